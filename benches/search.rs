@@ -0,0 +1,99 @@
+//! Measures the search speed (in nodes per second) on [`EXAMPLE_PUZZLES`],
+//! with `cargo bench`. Every puzzle is searched until the node budget runs out,
+//! so the heuristics' own quality does not matter.
+//!
+//! The default pattern DB is only measured when both of its files are given,
+//! as written by `puzzle24 build-db`:
+//! `PUZZLE24_PATTERN0=pattern0.bin PUZZLE24_PATTERN1=pattern1.bin cargo bench`
+//!
+//! # Baselines
+//!
+//! In million nodes per second, on a single core (of the same machine, with
+//! the best of several runs, which vary by about 10%). The searches copied the
+//! grid for every frame, before they applied and undid moves in place. `as
+//! SearchProblem` is [`solve_ida_problem`] on the [`SlidingPuzzle`], and
+//! `with BPMX` is [`solve_ita_bpmx`].
+//!
+//! | Search                  | Copying grids | In place | Current |
+//! |-------------------------|---------------|----------|---------|
+//! | ManhattanDistance       |          11.9 |     11.3 |    54.2 |
+//! | RecomputedManhattan     |          26.2 |     26.8 |    26.2 |
+//! | PatternDB (4-4-4-4-4-4) |           9.2 |     10.0 |     9.9 |
+//! |   as SearchProblem      |           9.2 |      8.8 |    10.6 |
+//! |   with BPMX             |             - |        - |    10.1 |
+//!
+//! `RecomputedManhattan` recomputes the distance for every move, as every
+//! heuristic did when grids were fixed at 5x5. (So, it measures the generic
+//! grid access, rather than the heuristic)
+
+// stdlib imports
+use std::env;
+use std::time::Duration;
+// external imports
+use puzzle24::{ArrayGrid24, Budget, DefaultPatternDB, EXAMPLE_PUZZLES, Goal, Heuristic, ManhattanDistance, PatternDB, PruningFsm, Puzzle, PuzzleCell, PuzzleGrid, SearchStats, SlidingPuzzle};
+use puzzle24::{solve_ida_problem, solve_ita_bpmx, solve_ita_budget};
+
+
+/// The nodes searched for every puzzle
+const NUM_NODES: usize = 5_000_000;
+
+/// The Manhattan distance, without incremental updates. (So, the search
+/// recomputes it for every move)
+struct RecomputedManhattan;
+
+impl Heuristic for RecomputedManhattan {
+  fn compute< P: PuzzleGrid >( &self, p: &P ) -> u8 {
+    let mut sum = 0;
+    for cell in 0..25 {
+      let tile = u8::from( p.get_tile( PuzzleCell::from( cell ) ) );
+      if tile != 0 {
+        sum += ( cell % 5 ).abs_diff( tile % 5 ) + ( cell / 5 ).abs_diff( tile / 5 );
+      }
+    }
+    sum
+  }
+}
+
+fn bench< H: Heuristic >( name: &str, h: &H ) {
+  bench_with( name, |puzzle, budget| solve_ita_budget( h, puzzle, budget ).1 );
+}
+
+/// Measures the search, which takes every puzzle with the node budget.
+fn bench_with( name: &str, solve: impl Fn( ArrayGrid24, Budget ) -> SearchStats ) {
+  let budget = Budget { max_steps: Some( NUM_NODES ), max_time: None };
+  let mut num_nodes = 0;
+  let mut elapsed = Duration::ZERO;
+
+  for puzzle in EXAMPLE_PUZZLES {
+    let stats = solve( puzzle, budget );
+    num_nodes += stats.num_steps;
+    elapsed += stats.elapsed;
+  }
+
+  println!(
+    "{:<24} {:>10} nodes in {:>6} ms  ({:.1} M nodes/s)",
+    name, num_nodes, elapsed.as_millis( ), num_nodes as f64 / elapsed.as_secs_f64( ) / 1e6
+  );
+}
+
+fn main( ) {
+  bench( "ManhattanDistance", &ManhattanDistance::new( ) );
+  bench( "RecomputedManhattan", &RecomputedManhattan );
+
+  // Small tables, which are built in seconds
+  let db = PatternDB::build( &[ &[1,2,3,4], &[5,6,7,8], &[9,10,11,12], &[13,14,15,16], &[17,18,19,20], &[21,22,23,24] ] );
+  bench( "PatternDB (4-4-4-4-4-4)", &db );
+  // The same table, through the generic search problem, and with BPMX
+  let goal = Goal::solved( );
+  let problem = SlidingPuzzle::new( &goal );
+  bench_with( "  as SearchProblem", |puzzle, budget| solve_ida_problem( &problem, &db, Puzzle::new( puzzle ), budget ).1 );
+  bench_with( "  with BPMX", |puzzle, budget| solve_ita_bpmx( &db, puzzle, &goal, &PruningFsm::SLIDING_INVERSES, budget ).1 );
+
+  match ( env::var_os( "PUZZLE24_PATTERN0" ), env::var_os( "PUZZLE24_PATTERN1" ) ) {
+    ( Some( pattern0 ), Some( pattern1 ) ) => {
+      let db = DefaultPatternDB::load( pattern0, pattern1 ).expect( "the pattern files are readable" );
+      bench( "DefaultPatternDB", &db );
+    },
+    _ => println!( "DefaultPatternDB         skipped (set PUZZLE24_PATTERN0 and PUZZLE24_PATTERN1)" ),
+  }
+}
@@ -0,0 +1,111 @@
+#ifndef PUZZLE24_H
+#define PUZZLE24_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The encoding of moves. (See [`Dir`])
+#define PUZZLE24_MOVE_UP 0
+
+#define PUZZLE24_MOVE_RIGHT 1
+
+#define PUZZLE24_MOVE_DOWN 2
+
+#define PUZZLE24_MOVE_LEFT 3
+
+// The result of every fallible function.
+typedef enum Puzzle24Status {
+  PUZZLE24_STATUS_OK = 0,
+  // A required pointer argument was null
+  PUZZLE24_STATUS_NULL_POINTER = 1,
+  // A path was not valid UTF-8
+  PUZZLE24_STATUS_INVALID_PATH = 2,
+  // The pattern files could not be read, or have the wrong size
+  PUZZLE24_STATUS_IO_ERROR = 3,
+  // The puzzle contains an out-of-range or duplicate tile
+  PUZZLE24_STATUS_INVALID_PUZZLE = 4,
+  // The puzzle cannot reach the solved puzzle
+  PUZZLE24_STATUS_UNSOLVABLE = 5,
+  // The solution does not fit in the caller-provided move buffer. (The
+  // required length is still written)
+  PUZZLE24_STATUS_BUFFER_TOO_SMALL = 6,
+  // The node or time budget ran out before a solution was found
+  PUZZLE24_STATUS_OUT_OF_BUDGET = 7,
+  // An internal error occurred. (This is a bug)
+  PUZZLE24_STATUS_INTERNAL = 8,
+  // The search found no solution of at most 255 moves. (Which every solvable
+  // puzzle has, so this is also a bug)
+  PUZZLE24_STATUS_NO_SOLUTION = 9,
+} Puzzle24Status;
+
+// An opaque handle to a loaded pattern database.
+typedef struct Puzzle24PatternDB Puzzle24PatternDB;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Loads the pattern database from the files written by
+// `puzzle24 build-db <pattern0> <pattern1>`. On success, `*out_db` holds a
+// handle, which must be released with [`puzzle24_db_free()`].
+//
+// # Safety
+//
+// The paths must be null or NUL-terminated strings. `out_db` must be null or
+// valid for writes.
+enum Puzzle24Status puzzle24_db_load(const char *pattern0_path,
+                                     const char *pattern1_path,
+                                     struct Puzzle24PatternDB **out_db);
+
+// Releases a pattern database. Does nothing if `db` is null.
+//
+// # Safety
+//
+// `db` must be null, or obtained from [`puzzle24_db_load()`] and not yet freed.
+void puzzle24_db_free(struct Puzzle24PatternDB *db);
+
+// Computes the (admissible) heuristic value of the puzzle into `*out_h`.
+//
+// # Safety
+//
+// `db` must be null or a live handle. `puzzle` must be null or point to 25
+// readable bytes. `out_h` must be null or valid for writes.
+enum Puzzle24Status puzzle24_heuristic(const struct Puzzle24PatternDB *db,
+                                       const uint8_t *puzzle,
+                                       uint8_t *out_h);
+
+// Finds an optimal solution with IDA*, and writes its moves to `out_moves`.
+// The solution length is written to `*out_len`, also when the buffer is too
+// small. (A buffer of 255 moves always suffices)
+//
+// `max_nodes` and `max_millis` bound the search; `0` means unlimited.
+//
+// This function may be called concurrently on the same database.
+//
+// # Safety
+//
+// `db` must be null or a live handle. `puzzle` must be null or point to 25
+// readable bytes. `out_moves` must be null or point to `moves_capacity`
+// writable bytes. `out_len` must be null or valid for writes.
+enum Puzzle24Status puzzle24_solve(const struct Puzzle24PatternDB *db,
+                                   const uint8_t *puzzle,
+                                   uint64_t max_nodes,
+                                   uint64_t max_millis,
+                                   uint8_t *out_moves,
+                                   size_t moves_capacity,
+                                   size_t *out_len);
+
+// Returns a static, NUL-terminated description of the status. (Which is
+// taken as an integer, so values which are no status get `"unknown status"`)
+const char *puzzle24_status_message(int status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PUZZLE24_H */
@@ -0,0 +1,281 @@
+//! A C-compatible interface to the solver. (Enabled by the `ffi` feature)
+//!
+//! The C header `include/puzzle24.h` is generated from this module with
+//! `cbindgen --config cbindgen.toml --output include/puzzle24.h`, which reads
+//! the sources. (So, it needs no features itself) After changing this module,
+//! check that it builds with `cargo build --features ffi`, and regenerate the
+//! header.
+//!
+//! Only C and C++ consumers need the shared library, which is built on request
+//! with `cargo rustc --release --lib --features ffi --crate-type cdylib`. (Into
+//! `target/release/libpuzzle24.so`, or the platform's equivalent)
+//!
+//! Puzzles are passed as 25 bytes, which contain the tiles in cell order, with
+//! `0` as the gap. Moves are written as bytes, with the values of [`Dir`]
+//! (i.e., `0` = up, `1` = right, `2` = down, `3` = left). They are the
+//! directions in which the tiles move. (See [`crate::solve_ita()`])
+
+// stdlib imports
+use std::ffi::{CStr, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+// local imports
+use crate::grid::{ArrayGrid24, Dir, InvalidPuzzle, validate_cells};
+use crate::heuristic::{DefaultPatternDB, Heuristic};
+use crate::search::{Budget, SearchOutcome, solve_ita_budget};
+
+
+// Note that these are literals (instead of `Dir::UP as u8`), as `cbindgen`
+// only understands those.
+
+/// The encoding of moves. (See [`Dir`])
+pub const PUZZLE24_MOVE_UP: u8 = 0;
+pub const PUZZLE24_MOVE_RIGHT: u8 = 1;
+pub const PUZZLE24_MOVE_DOWN: u8 = 2;
+pub const PUZZLE24_MOVE_LEFT: u8 = 3;
+
+const _: () =
+  assert!(
+    PUZZLE24_MOVE_UP == Dir::UP as u8 && PUZZLE24_MOVE_RIGHT == Dir::RIGHT as u8
+    && PUZZLE24_MOVE_DOWN == Dir::DOWN as u8 && PUZZLE24_MOVE_LEFT == Dir::LEFT as u8
+  );
+
+/// An opaque handle to a loaded pattern database.
+pub struct Puzzle24PatternDB( DefaultPatternDB );
+
+/// The result of every fallible function.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Puzzle24Status {
+  Ok = 0,
+  /// A required pointer argument was null
+  NullPointer = 1,
+  /// A path was not valid UTF-8
+  InvalidPath = 2,
+  /// The pattern files could not be read, or have the wrong size
+  IoError = 3,
+  /// The puzzle contains an out-of-range or duplicate tile
+  InvalidPuzzle = 4,
+  /// The puzzle cannot reach the solved puzzle
+  Unsolvable = 5,
+  /// The solution does not fit in the caller-provided move buffer. (The
+  /// required length is still written)
+  BufferTooSmall = 6,
+  /// The node or time budget ran out before a solution was found
+  OutOfBudget = 7,
+  /// An internal error occurred. (This is a bug)
+  Internal = 8,
+  /// The search found no solution of at most 255 moves. (Which every solvable
+  /// puzzle has, so this is also a bug)
+  NoSolution = 9,
+}
+
+impl Puzzle24Status {
+  const ALL: [Puzzle24Status; 10] =
+    [ Puzzle24Status::Ok, Puzzle24Status::NullPointer, Puzzle24Status::InvalidPath
+    , Puzzle24Status::IoError, Puzzle24Status::InvalidPuzzle, Puzzle24Status::Unsolvable
+    , Puzzle24Status::BufferTooSmall, Puzzle24Status::OutOfBudget, Puzzle24Status::Internal
+    , Puzzle24Status::NoSolution
+    ];
+
+  /// Returns the status with the value, if any. (C callers may pass any
+  /// integer for an enum)
+  fn from_c_int( status: c_int ) -> Option< Self > {
+    Puzzle24Status::ALL.into_iter( ).find( |&s| s as c_int == status )
+  }
+}
+
+/// Loads the pattern database from the files written by
+/// `puzzle24 build-db <pattern0> <pattern1>`. On success, `*out_db` holds a
+/// handle, which must be released with [`puzzle24_db_free()`].
+///
+/// # Safety
+///
+/// The paths must be null or NUL-terminated strings. `out_db` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn puzzle24_db_load(
+  pattern0_path: *const c_char
+, pattern1_path: *const c_char
+, out_db: *mut *mut Puzzle24PatternDB
+) -> Puzzle24Status {
+  if pattern0_path.is_null( ) || pattern1_path.is_null( ) || out_db.is_null( ) {
+    return Puzzle24Status::NullPointer;
+  }
+
+  let (Ok( path0 ), Ok( path1 )) =
+    ( CStr::from_ptr( pattern0_path ).to_str( ), CStr::from_ptr( pattern1_path ).to_str( ) )
+  else {
+    return Puzzle24Status::InvalidPath;
+  };
+
+  catch_panic( || {
+    match DefaultPatternDB::load( path0, path1 ) {
+      Ok( db ) => {
+        *out_db = Box::into_raw( Box::new( Puzzle24PatternDB( db ) ) );
+        Puzzle24Status::Ok
+      },
+      Err( _ ) => Puzzle24Status::IoError,
+    }
+  } )
+}
+
+/// Releases a pattern database. Does nothing if `db` is null.
+///
+/// # Safety
+///
+/// `db` must be null, or obtained from [`puzzle24_db_load()`] and not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn puzzle24_db_free( db: *mut Puzzle24PatternDB ) {
+  if !db.is_null( ) {
+    drop( Box::from_raw( db ) );
+  }
+}
+
+/// Computes the (admissible) heuristic value of the puzzle into `*out_h`.
+///
+/// # Safety
+///
+/// `db` must be null or a live handle. `puzzle` must be null or point to 25
+/// readable bytes. `out_h` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn puzzle24_heuristic(
+  db: *const Puzzle24PatternDB
+, puzzle: *const u8
+, out_h: *mut u8
+) -> Puzzle24Status {
+  if db.is_null( ) || puzzle.is_null( ) || out_h.is_null( ) {
+    return Puzzle24Status::NullPointer;
+  }
+
+  let grid =
+    match read_puzzle( puzzle ) {
+      Ok( grid ) => grid,
+      Err( status ) => return status,
+    };
+
+  catch_panic( || {
+    *out_h = ( *db ).0.compute( &grid );
+    Puzzle24Status::Ok
+  } )
+}
+
+/// Finds an optimal solution with IDA*, and writes its moves to `out_moves`.
+/// The solution length is written to `*out_len`, also when the buffer is too
+/// small. (A buffer of 255 moves always suffices)
+///
+/// `max_nodes` and `max_millis` bound the search; `0` means unlimited.
+///
+/// This function may be called concurrently on the same database.
+///
+/// # Safety
+///
+/// `db` must be null or a live handle. `puzzle` must be null or point to 25
+/// readable bytes. `out_moves` must be null or point to `moves_capacity`
+/// writable bytes. `out_len` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn puzzle24_solve(
+  db: *const Puzzle24PatternDB
+, puzzle: *const u8
+, max_nodes: u64
+, max_millis: u64
+, out_moves: *mut u8
+, moves_capacity: usize
+, out_len: *mut usize
+) -> Puzzle24Status {
+  if db.is_null( ) || puzzle.is_null( ) || out_len.is_null( ) || ( out_moves.is_null( ) && moves_capacity > 0 ) {
+    return Puzzle24Status::NullPointer;
+  }
+
+  let grid =
+    match read_puzzle( puzzle ) {
+      Ok( grid ) => grid,
+      Err( status ) => return status,
+    };
+
+  let budget =
+    Budget {
+      max_steps: if max_nodes == 0 { None } else { Some( usize::try_from( max_nodes ).unwrap_or( usize::MAX ) ) },
+      max_time: if max_millis == 0 { None } else { Some( Duration::from_millis( max_millis ) ) },
+    };
+
+  catch_panic( || {
+    match solve_ita_budget( &( *db ).0, grid, budget ).0 {
+      SearchOutcome::Solved( path ) => {
+        *out_len = path.len( );
+
+        if path.len( ) > moves_capacity {
+          return Puzzle24Status::BufferTooSmall;
+        }
+        for (i, dir) in path.into_iter( ).enumerate( ) {
+          out_moves.add( i ).write( dir as u8 );
+        }
+        Puzzle24Status::Ok
+      },
+      SearchOutcome::Exhausted => Puzzle24Status::NoSolution,
+      SearchOutcome::OutOfBudget => Puzzle24Status::OutOfBudget,
+    }
+  } )
+}
+
+/// Returns a static, NUL-terminated description of the status. (Which is
+/// taken as an integer, so values which are no status get `"unknown status"`)
+#[no_mangle]
+pub extern "C" fn puzzle24_status_message( status: c_int ) -> *const c_char {
+  let msg: &'static CStr =
+    match Puzzle24Status::from_c_int( status ) {
+      Some( Puzzle24Status::Ok )             => c"ok",
+      Some( Puzzle24Status::NullPointer )    => c"null pointer argument",
+      Some( Puzzle24Status::InvalidPath )    => c"path is not valid UTF-8",
+      Some( Puzzle24Status::IoError )        => c"could not read pattern files",
+      Some( Puzzle24Status::InvalidPuzzle )  => c"puzzle contains an out-of-range or duplicate tile",
+      Some( Puzzle24Status::Unsolvable )     => c"puzzle is unsolvable",
+      Some( Puzzle24Status::BufferTooSmall ) => c"move buffer is too small",
+      Some( Puzzle24Status::OutOfBudget )    => c"budget exceeded",
+      Some( Puzzle24Status::Internal )       => c"internal error",
+      Some( Puzzle24Status::NoSolution )     => c"no solution within 255 moves",
+      None                                   => c"unknown status",
+    };
+  msg.as_ptr( )
+}
+
+/// Reads and validates 25 tiles.
+///
+/// # Safety
+///
+/// `puzzle` must point to 25 readable bytes.
+unsafe fn read_puzzle( puzzle: *const u8 ) -> Result< ArrayGrid24, Puzzle24Status > {
+  let cells: [u8; 25] = puzzle.cast::< [u8; 25] >( ).read_unaligned( );
+
+  match validate_cells( &cells ) {
+    Ok( () ) => Ok( ArrayGrid24::from( cells ) ),
+    Err( InvalidPuzzle::Unsolvable ) => Err( Puzzle24Status::Unsolvable ),
+    Err( _ ) => Err( Puzzle24Status::InvalidPuzzle ),
+  }
+}
+
+/// Unwinding into C is undefined behaviour, so we report panics as a status.
+fn catch_panic< F: FnOnce( ) -> Puzzle24Status >( f: F ) -> Puzzle24Status {
+  panic::catch_unwind( AssertUnwindSafe( f ) ).unwrap_or( Puzzle24Status::Internal )
+}
+
+#[cfg(test)]
+mod tests {
+  // stdlib imports
+  use std::ffi::{CStr, c_int};
+  // local imports
+  use super::{Puzzle24Status, puzzle24_status_message};
+
+  #[test]
+  fn every_status_has_a_message( ) {
+    let message = |status: c_int| unsafe { CStr::from_ptr( puzzle24_status_message( status ) ) };
+
+    for status in Puzzle24Status::ALL {
+      assert_ne!( message( status as c_int ), c"unknown status" );
+    }
+    assert_eq!( message( Puzzle24Status::NoSolution as c_int ), c"no solution within 255 moves" );
+    for status in [ -1, 10, c_int::MAX ] {
+      assert_eq!( message( status ), c"unknown status" );
+    }
+  }
+}
@@ -1,54 +1,133 @@
+// stdlib imports
+use std::fmt;
 // local imports
 use super::general::*;
+use super::validation::{assert_valid_cells, assert_valid_rows};
 
 
+/// Stores every tile in its own byte.
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
 #[repr(transparent)]
-pub struct ArrayGrid24( [PuzzleTile; 25] );
+pub struct ArrayGrid< const WIDTH: usize = 5, const HEIGHT: usize = 5 >( [[PuzzleTile< WIDTH, HEIGHT >; WIDTH]; HEIGHT] );
+
+/// The grid of a 24-puzzle.
+pub type ArrayGrid24 = ArrayGrid< 5, 5 >;
+
+impl< const WIDTH: usize, const HEIGHT: usize > ArrayGrid< WIDTH, HEIGHT > {
+  /// Constructs the grid, and panics if the rows do not form a solvable
+  /// puzzle. In a `const` context, that is a compilation error.
+  #[inline]
+  #[track_caller]
+  pub const fn from_rows( rows: [[u8; WIDTH]; HEIGHT] ) -> Self {
+    assert_valid_rows( &rows );
+    ArrayGrid::from_rows_unchecked( rows )
+  }
+
+  /// Note that this only validates the rows in debug builds. (See
+  /// [`ArrayGrid::from_rows()`])
+  #[inline]
+  pub const fn from_rows_unchecked( rows: [[u8; WIDTH]; HEIGHT] ) -> Self {
+    let mut out = [[PuzzleTile::GAP; WIDTH]; HEIGHT];
+
+    // Note: for-loops are disallowed in `const fn`s
+    let mut y = 0;
+    while y < HEIGHT {
+      let mut x = 0;
+      while x < WIDTH {
+        // Note that this call performs input validation.
+        out[ y ][ x ] = PuzzleTile::from_const( rows[ y ][ x ] );
+        x += 1;
+      }
+      y += 1;
+    }
+
+    ArrayGrid( out )
+  }
+
+  /// Copies the tiles of any grid. (Which are valid already)
+  #[inline]
+  pub fn from_grid< P: PuzzleGrid< WIDTH, HEIGHT > >( p: &P ) -> Self {
+    let mut out = [[PuzzleTile::GAP; WIDTH]; HEIGHT];
+
+    for (cell_idx, out_tile) in out.as_flattened_mut( ).iter_mut( ).enumerate( ) {
+      *out_tile = p.get_tile( PuzzleCell::from( cell_idx as u8 ) );
+    }
+    ArrayGrid( out )
+  }
+}
 
 impl ArrayGrid24 {
+  /// Constructs the grid, and panics if the cells do not form a solvable
+  /// 24-puzzle. In a `const` context, that is a compilation error. (See also
+  /// [`crate::puzzle24!`])
+  #[inline]
+  #[track_caller]
+  pub const fn from_cells( cells: [u8; 25] ) -> Self {
+    assert_valid_cells( &cells );
+    ArrayGrid24::from_cells_unchecked( cells )
+  }
+
+  /// Note that this only validates the cells in debug builds. (See
+  /// [`ArrayGrid24::from_cells()`])
   #[inline]
   pub const fn from_cells_unchecked( cells: [u8; 25] ) -> Self {
-    let mut out = [PuzzleTile::from_const( 0u8 ); 25];
+    let mut out = [[PuzzleTile::GAP; 5]; 5];
 
     // Note: for-loops are disallowed in `const fn`s
     let mut cell_idx = 0;
     while cell_idx < 25 {
       let tile = cells[ cell_idx ];
       // Note that this call performs input validation.
-      out[ cell_idx ] = PuzzleTile::from_const( tile as u8 );
+      out[ cell_idx / 5 ][ cell_idx % 5 ] = PuzzleTile::from_const( tile );
       cell_idx += 1;
     }
 
-    ArrayGrid24( out )
+    ArrayGrid( out )
   }
 }
 
-impl Puzzle24Grid for ArrayGrid24 {
+impl< const WIDTH: usize, const HEIGHT: usize > PuzzleGrid< WIDTH, HEIGHT > for ArrayGrid< WIDTH, HEIGHT > {
   #[inline]
-  fn get_tile( &self, cell: PuzzleCell ) -> PuzzleTile {
+  fn get_tile( &self, cell: PuzzleCell< WIDTH, HEIGHT > ) -> PuzzleTile< WIDTH, HEIGHT > {
     // This is safe under our range invariant on `PuzzleCell`
-    unsafe { *self.0.get_unchecked( u8::from( cell ) as usize ) }
+    unsafe { *self.0.as_flattened( ).get_unchecked( u8::from( cell ) as usize ) }
   }
 
   #[inline]
-  fn set_tile( &mut self, cell: PuzzleCell, tile: PuzzleTile ) {
-    let cell_ref = unsafe { self.0.get_unchecked_mut( u8::from( cell ) as usize ) };
+  fn set_tile( &mut self, cell: PuzzleCell< WIDTH, HEIGHT >, tile: PuzzleTile< WIDTH, HEIGHT > ) {
+    let cell_ref = unsafe { self.0.as_flattened_mut( ).get_unchecked_mut( u8::from( cell ) as usize ) };
     *cell_ref = tile;
   }
 }
 
+impl< const WIDTH: usize, const HEIGHT: usize > fmt::Debug for ArrayGrid< WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    PuzzleGrid::fmt( self, f )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > From< [[u8; WIDTH]; HEIGHT] > for ArrayGrid< WIDTH, HEIGHT > {
+  fn from( v: [[u8; WIDTH]; HEIGHT] ) -> Self {
+    let mut out = [[PuzzleTile::GAP; WIDTH]; HEIGHT];
+
+    for (out_tile, &tile) in out.as_flattened_mut( ).iter_mut( ).zip( v.as_flattened( ) ) {
+      // note that `PuzzleTile::from` does input validation
+      *out_tile = PuzzleTile::from( tile );
+    }
+
+    ArrayGrid( out )
+  }
+}
+
 impl From< [u8; 25] > for ArrayGrid24 {
   fn from( v: [u8; 25] ) -> Self {
-    let mut out = [PuzzleTile::from( 0u8 ); 25];
+    let mut out = [[PuzzleTile::GAP; 5]; 5];
 
-    // Note: for-loops are disallowed in `const fn`s
-    for cell_idx in 0..25 {
-      let tile = v[ cell_idx ];
+    for (out_tile, &tile) in out.as_flattened_mut( ).iter_mut( ).zip( v.iter( ) ) {
       // note that `PuzzleTile::from` does input validation
-      out[ cell_idx ] = PuzzleTile::from( tile as u8 );
+      *out_tile = PuzzleTile::from( tile );
     }
 
-    ArrayGrid24( out )
+    ArrayGrid( out )
   }
 }
@@ -0,0 +1,438 @@
+//! Boards with blocked cells. (e.g., pillars on a warehouse floor) The tiles of
+//! blocked cells never move, as [`crate::solve_ita_board()`] never moves the gap
+//! into them. Heuristics are built on the board. (e.g., [`crate::BoardDistance`],
+//! [`crate::PatternDB::build_on_board()`] or
+//! [`crate::WeightedPatternDB::build_on_board()`]) As the parity rule no longer
+//! suffices, [`Board::validate_rows()`] checks solvability on the open cells.
+//!
+//! On a torus (see [`Board::torus()`]), the gap moves off one edge onto the
+//! opposite edge. [`crate::solve_ita_board()`] solves it with the
+//! [`crate::TorusManhattanDistance`], or with heuristics built on the torus.
+//! Unless both sides are even, every arrangement is solvable, which
+//! [`Board::validate_rows()`] also checks.
+
+// stdlib imports
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+// local imports
+use super::general::*;
+use super::goal::Goal;
+use super::validation::{InvalidPuzzle, validate_permutation};
+
+
+/// How the edges of a board connect.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology {
+  /// The gap never moves past an edge. (i.e., the regular puzzle)
+  #[default]
+  Flat,
+  /// The gap moves off one edge onto the opposite edge. (See
+  /// [`PuzzleCell::step_wrapping()`])
+  Torus,
+}
+
+/// A board with blocked cells, which are permanently unavailable. (e.g.,
+/// pillars on a warehouse floor) The gap never moves into a blocked cell, so
+/// the tile in a blocked cell never moves. (It's a fixed obstacle) The board's
+/// [`Topology`] determines whether the gap wraps around its edges.
+///
+/// By default, no cells are blocked, and the board is flat.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Board< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// Whether every cell is blocked. (Grids have at most 256 cells; see
+  /// [`PuzzleCell::NUM_CELLS`])
+  is_blocked: [bool; 256],
+  topology: Topology,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Board< WIDTH, HEIGHT > {
+  /// The maximum number of arrangements which [`Board::validate_rows()`]
+  /// enumerates for regions with a cut cell.
+  pub const MAX_ENUMERATED: usize = 1 << 18;
+
+  /// The board without blocked cells. (i.e., the regular puzzle)
+  pub const fn open( ) -> Self {
+    Board { is_blocked: [false; 256], topology: Topology::Flat }
+  }
+
+  /// The torus without blocked cells. (i.e., the wrap-around puzzle)
+  pub const fn torus( ) -> Self {
+    Board::open( ).with_topology( Topology::Torus )
+  }
+
+  /// Returns the board with the given topology, and the same blocked cells.
+  pub const fn with_topology( mut self, topology: Topology ) -> Self {
+    self.topology = topology;
+    self
+  }
+
+  /// Takes, for every cell (by row), whether it is blocked.
+  pub const fn from_rows( rows: &[[bool; WIDTH]; HEIGHT] ) -> Self {
+    let mut is_blocked = [false; 256];
+
+    // Note: for-loops are disallowed in `const fn`s
+    let mut cell_idx = 0;
+    while cell_idx < PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      is_blocked[ cell_idx ] = rows.as_flattened( )[ cell_idx ];
+      cell_idx += 1;
+    }
+    Board { is_blocked, topology: Topology::Flat }
+  }
+
+  /// Returns whether the cell is blocked.
+  #[inline]
+  pub const fn is_blocked( &self, cell: PuzzleCell< WIDTH, HEIGHT > ) -> bool {
+    self.is_blocked[ cell.to_u8( ) as usize ]
+  }
+
+  #[inline]
+  pub const fn topology( &self ) -> Topology {
+    self.topology
+  }
+
+  /// Returns whether no cell is blocked.
+  pub fn is_open( &self ) -> bool {
+    !self.is_blocked[ ..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ].contains( &true )
+  }
+
+  /// Like [`PuzzleCell::step()`], but returns `None` for a blocked cell. On a
+  /// torus, this wraps around the edges. (Unless it returns to the cell itself,
+  /// on a board with a single row or column)
+  #[inline]
+  pub const fn step( &self, cell: PuzzleCell< WIDTH, HEIGHT >, dir: Dir ) -> Option< PuzzleCell< WIDTH, HEIGHT > > {
+    let adj_cell =
+      match self.topology {
+        Topology::Flat => cell.step( dir ),
+        Topology::Torus => {
+          let adj_cell = cell.step_wrapping( dir );
+          if adj_cell.to_u8( ) != cell.to_u8( ) { Some( adj_cell ) } else { None }
+        },
+      };
+
+    match adj_cell {
+      Some( adj_cell ) if !self.is_blocked( adj_cell ) => Some( adj_cell ),
+      _ => None,
+    }
+  }
+
+  /// Checks whether the rows form a puzzle which can reach the goal on this
+  /// board. (See [`Goal::validate_rows()`] for the open board)
+  ///
+  /// The gap must be in an open cell, and tiles in blocked cells must be in
+  /// their goal cells. The open cells fall apart into connected regions, of
+  /// which only the gap's region changes. For that region:
+  ///
+  /// * If it is a cycle, the tiles only rotate along it. So, their cyclic
+  ///   order must match the goal's.
+  /// * If it has no cut cell (i.e., whose removal disconnects the region),
+  ///   Wilson's theorem applies. If the region is bipartite (e.g., any region
+  ///   of a flat board, or of a torus with even sides), every arrangement
+  ///   which satisfies the parity rule is reachable. The gap's distance then
+  ///   has the parity of the difference in colour between its cells. (Which,
+  ///   on a flat board, is that of their Manhattan distance) Otherwise (e.g.,
+  ///   on a torus with an odd side), a cycle of odd length lets the gap flip
+  ///   the parity, so every arrangement is reachable. (Except for Wilson's
+  ///   graph θ₀, of seven cells. So, we enumerate regions of seven cells, as
+  ///   below)
+  /// * Otherwise (e.g., with a corridor), tiles can get stuck on either side
+  ///   of a cut cell. We enumerate up to [`Board::MAX_ENUMERATED`]
+  ///   arrangements of the region. For larger regions, we only check the
+  ///   parity rule (if the region is bipartite), which is necessary but not
+  ///   sufficient. If it holds, this returns [`InvalidPuzzle::Undecided`].
+  pub fn validate_rows( &self, rows: &[[u8; WIDTH]; HEIGHT], goal: &Goal< WIDTH, HEIGHT > ) -> Result< (), InvalidPuzzle > {
+    validate_permutation( rows.as_flattened( ) )?;
+
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let tiles = rows.as_flattened( );
+    let goal_tiles = goal.to_rows( );
+    let goal_tiles = goal_tiles.as_flattened( );
+    let gap_cell = tiles.iter( ).position( |&t| t == 0 ).unwrap_or( 0 );
+
+    // The gap must be in an open cell, and tiles outside its region never move
+    if self.is_blocked[ gap_cell ] {
+      return Err( InvalidPuzzle::Unsolvable );
+    }
+    let region = self.region( PuzzleCell::from( gap_cell as u8 ), &[] );
+    let mut is_in_region = [false; 256];
+    for &cell in &region {
+      is_in_region[ u8::from( cell ) as usize ] = true;
+    }
+    if ( 0..num_cells ).any( |c| !is_in_region[ c ] && tiles[ c ] != goal_tiles[ c ] ) {
+      return Err( InvalidPuzzle::Unsolvable );
+    }
+
+    if self.is_cycle( &region ) {
+      self.validate_cycle( &region, tiles, goal_tiles )
+    } else if region.len( ) <= 2 || ( region.len( ) != 7 && !self.has_cut_cell( &region ) ) {
+      self.validate_parity( &region, rows, goal )
+    } else {
+      self.validate_enumerated( &region, tiles, goal_tiles ).unwrap_or_else( || {
+        self.validate_parity( &region, rows, goal ).and( Err( InvalidPuzzle::Undecided ) )
+      } )
+    }
+  }
+
+  /// Returns the open cells connected to `start` (in breadth-first order), while
+  /// treating the `excluded` cells as blocked.
+  fn region( &self, start: PuzzleCell< WIDTH, HEIGHT >, excluded: &[PuzzleCell< WIDTH, HEIGHT >] ) -> Vec< PuzzleCell< WIDTH, HEIGHT > > {
+    let mut is_visited = [false; 256];
+    let mut region = vec![ start ];
+    is_visited[ u8::from( start ) as usize ] = true;
+
+    let mut i = 0;
+    while i < region.len( ) {
+      let cell = region[ i ];
+      for dir in Dir::ALL {
+        if let Some( adj_cell ) = self.step( cell, dir ) {
+          if !is_visited[ u8::from( adj_cell ) as usize ] && !excluded.contains( &adj_cell ) {
+            is_visited[ u8::from( adj_cell ) as usize ] = true;
+            region.push( adj_cell );
+          }
+        }
+      }
+      i += 1;
+    }
+    region
+  }
+
+  /// Returns the open neighbours of the cell. (On a small torus, two
+  /// directions may lead to the same neighbour)
+  fn neighbours( &self, cell: PuzzleCell< WIDTH, HEIGHT > ) -> Vec< PuzzleCell< WIDTH, HEIGHT > > {
+    let mut neighbours = Vec::with_capacity( 4 );
+    for dir in Dir::ALL {
+      if let Some( adj_cell ) = self.step( cell, dir ) {
+        if !neighbours.contains( &adj_cell ) {
+          neighbours.push( adj_cell );
+        }
+      }
+    }
+    neighbours
+  }
+
+  /// Returns whether the (connected) region is a cycle. (i.e., every cell has
+  /// exactly two neighbours)
+  fn is_cycle( &self, region: &[PuzzleCell< WIDTH, HEIGHT >] ) -> bool {
+    region.len( ) >= 3 && region.iter( ).all( |&cell| self.neighbours( cell ).len( ) == 2 )
+  }
+
+  /// Checks the parity rule on the (connected) region. (See
+  /// [`Board::validate_rows()`])
+  fn validate_parity( &self, region: &[PuzzleCell< WIDTH, HEIGHT >], rows: &[[u8; WIDTH]; HEIGHT], goal: &Goal< WIDTH, HEIGHT > ) -> Result< (), InvalidPuzzle > {
+    // Colour the region's cells, in breadth-first order
+    let mut colours = [None; 256];
+    colours[ u8::from( region[ 0 ] ) as usize ] = Some( 0 );
+
+    for &cell in region {
+      let colour = colours[ u8::from( cell ) as usize ].unwrap_or( 0 );
+
+      for adj_cell in self.neighbours( cell ) {
+        match colours[ u8::from( adj_cell ) as usize ] {
+          None => colours[ u8::from( adj_cell ) as usize ] = Some( 1 - colour ),
+          Some( c ) if c == colour => return Ok( () ), // it's not bipartite
+          Some( _ ) => { },
+        }
+      }
+    }
+
+    let gap_cell = rows.as_flattened( ).iter( ).position( |&t| t == 0 ).unwrap_or( 0 );
+    let gap_colour = colours[ gap_cell ].unwrap_or( 0 );
+    let goal_gap_colour = colours[ u8::from( goal.gap_cell( ) ) as usize ].unwrap_or( 0 );
+
+    if goal.permutation_parity( rows ) == gap_colour ^ goal_gap_colour {
+      Ok( () )
+    } else {
+      Err( InvalidPuzzle::Unsolvable )
+    }
+  }
+
+  /// Returns whether removing a cell disconnects the (connected) region.
+  fn has_cut_cell( &self, region: &[PuzzleCell< WIDTH, HEIGHT >] ) -> bool {
+    region.iter( ).any( |&cell| {
+      let start = if cell == region[ 0 ] { region[ 1 ] } else { region[ 0 ] };
+      self.region( start, &[cell] ).len( ) < region.len( ) - 1
+    } )
+  }
+
+  /// Compares the cyclic orders of the tiles (without the gap) along the
+  /// cycle.
+  fn validate_cycle( &self, region: &[PuzzleCell< WIDTH, HEIGHT >], tiles: &[u8], goal_tiles: &[u8] ) -> Result< (), InvalidPuzzle > {
+    // Walk along the cycle, starting in either direction
+    let mut order = vec![ u8::from( region[ 0 ] ) as usize ];
+    let mut prev = region[ 0 ];
+    let mut cell = region[ 1 ];
+    while cell != region[ 0 ] {
+      order.push( u8::from( cell ) as usize );
+      let next = self.neighbours( cell ).into_iter( ).find( |&c| c != prev ).expect( "cells on a cycle have two neighbours" );
+      (prev, cell) = (cell, next);
+    }
+
+    let seq: Vec< u8 > = order.iter( ).map( |&c| tiles[ c ] ).filter( |&t| t != 0 ).collect( );
+    let goal_seq: Vec< u8 > = order.iter( ).map( |&c| goal_tiles[ c ] ).filter( |&t| t != 0 ).collect( );
+    let offset = seq.iter( ).position( |&t| Some( &t ) == goal_seq.first( ) ).unwrap_or( 0 );
+
+    if ( 0..seq.len( ) ).all( |i| seq[ ( i + offset ) % seq.len( ) ] == goal_seq[ i ] ) {
+      Ok( () )
+    } else {
+      Err( InvalidPuzzle::Unsolvable )
+    }
+  }
+
+  /// Enumerates the arrangements of the region which are reachable from the
+  /// puzzle. Returns `None` if there are more than [`Board::MAX_ENUMERATED`].
+  fn validate_enumerated( &self, region: &[PuzzleCell< WIDTH, HEIGHT >], tiles: &[u8], goal_tiles: &[u8] ) -> Option< Result< (), InvalidPuzzle > > {
+    // Arrangements contain the tiles of the region's cells, in order
+    let mut region_idxs = [0usize; 256];
+    for (i, &cell) in region.iter( ).enumerate( ) {
+      region_idxs[ u8::from( cell ) as usize ] = i;
+    }
+    let adjacent: Vec< Vec< usize > > =
+      region.iter( ).map( |&cell| self.neighbours( cell ).iter( ).map( |&c| region_idxs[ u8::from( c ) as usize ] ).collect( ) ).collect( );
+
+    let start: Vec< u8 > = region.iter( ).map( |&cell| tiles[ u8::from( cell ) as usize ] ).collect( );
+    let goal: Vec< u8 > = region.iter( ).map( |&cell| goal_tiles[ u8::from( cell ) as usize ] ).collect( );
+
+    let mut visited = HashSet::new( );
+    let mut queue = VecDeque::new( );
+    visited.insert( start.clone( ) );
+    queue.push_back( start );
+
+    while let Some( arrangement ) = queue.pop_front( ) {
+      if arrangement == goal {
+        return Some( Ok( () ) );
+      }
+
+      let gap_idx = arrangement.iter( ).position( |&t| t == 0 ).unwrap_or( 0 );
+      for &adj_idx in &adjacent[ gap_idx ] {
+        let mut next = arrangement.clone( );
+        next.swap( gap_idx, adj_idx );
+
+        if visited.insert( next.clone( ) ) {
+          if visited.len( ) > Self::MAX_ENUMERATED {
+            return None;
+          }
+          queue.push_back( next );
+        }
+      }
+    }
+    Some( Err( InvalidPuzzle::Unsolvable ) )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Default for Board< WIDTH, HEIGHT > {
+  #[inline]
+  fn default( ) -> Self {
+    Board::open( )
+  }
+}
+
+/// Shows blocked cells as `#`, and open cells as `.`, by row. (Followed by the
+/// topology, for a torus)
+impl< const WIDTH: usize, const HEIGHT: usize > fmt::Debug for Board< WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!( f, "Board(" )?;
+
+    for y in 0..HEIGHT {
+      if y > 0 {
+        write!( f, " " )?;
+      }
+      for x in 0..WIDTH {
+        let cell = PuzzleCell::< WIDTH, HEIGHT >::from( (x as u8, y as u8) );
+        write!( f, "{}", if self.is_blocked( cell ) { '#' } else { '.' } )?;
+      }
+    }
+    if self.topology != Topology::Flat {
+      write!( f, ", {:?}", self.topology )?;
+    }
+    write!( f, ")" )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{ArrayGrid, Goal};
+  use crate::grid::validation::InvalidPuzzle;
+  use crate::testing::{bfs_distances, permutations};
+  use super::{Board, Topology};
+
+  /// Checks every arrangement of the open cells' tiles (with the other tiles
+  /// in their goal cells) against the grids which reach the goal.
+  fn check_board< const WIDTH: usize, const HEIGHT: usize >( board: &Board< WIDTH, HEIGHT >, goal: &Goal< WIDTH, HEIGHT > ) {
+    check_board_sampled( board, goal, 1 );
+  }
+
+  /// Like `check_board()`, but only checks every `step`th arrangement.
+  fn check_board_sampled< const WIDTH: usize, const HEIGHT: usize >( board: &Board< WIDTH, HEIGHT >, goal: &Goal< WIDTH, HEIGHT >, step: usize ) {
+    let distances = bfs_distances( goal, board );
+    let goal_rows = goal.to_rows( );
+    let goal_tiles = goal_rows.as_flattened( );
+    let open_cells: Vec< usize > = ( 0..WIDTH * HEIGHT ).filter( |&c| !board.is_blocked[ c ] ).collect( );
+
+    for permutation in permutations( open_cells.len( ) ).into_iter( ).step_by( step ) {
+      let mut rows = goal_rows;
+      for (&cell, &i) in open_cells.iter( ).zip( &permutation ) {
+        rows.as_flattened_mut( )[ cell ] = goal_tiles[ open_cells[ i as usize ] ];
+      }
+      let expected = if distances.contains_key( &ArrayGrid::from( rows ) ) { Ok( () ) } else { Err( InvalidPuzzle::Unsolvable ) };
+      assert_eq!( board.validate_rows( &rows, goal ), expected, "{:?} on {:?}", rows, board );
+    }
+  }
+
+  #[test]
+  fn blocked_boards_match_bfs( ) {
+    let o = false;
+    let x = true;
+    // Without a cut cell
+    check_board( &Board::< 3, 3 >::from_rows( &[[o, o, o], [o, o, o], [o, o, x]] ), &Goal::solved( ) );
+    // A cycle around a pillar
+    check_board( &Board::< 3, 3 >::from_rows( &[[o, o, o], [o, x, o], [o, o, o]] ), &Goal::solved( ) );
+    // With a corridor between the top and bottom rows
+    check_board( &Board::< 3, 3 >::from_rows( &[[o, o, o], [x, o, x], [o, o, o]] ), &Goal::solved( ) );
+    check_board( &Board::< 3, 3 >::from_rows( &[[o, o, o], [x, o, x], [o, o, o]] ), &Goal::snake( ) );
+    check_board( &Board::< 4, 2 >::from_rows( &[[o, o, o, x], [x, o, o, o]] ), &Goal::solved( ) );
+    // A dead end
+    check_board( &Board::< 2, 3 >::from_rows( &[[o, o], [o, o], [o, x]] ), &Goal::solved( ) );
+    check_board( &Board::< 4, 1 >::from_rows( &[[o, o, x, o]] ), &Goal::solved( ) );
+  }
+
+  #[test]
+  fn odd_tori_match_bfs( ) {
+    check_board_sampled( &Board::< 3, 3 >::torus( ), &Goal::solved( ), 5 );
+    check_board( &Board::< 2, 3 >::torus( ), &Goal::solved( ) );
+    check_board( &Board::< 3, 2 >::torus( ), &Goal::snake( ) );
+
+    // Regions of seven cells are enumerated
+    let o = false;
+    let x = true;
+    let board = Board::< 3, 3 >::from_rows( &[[o, o, o], [o, x, x], [o, o, o]] ).with_topology( Topology::Torus );
+    check_board_sampled( &board, &Goal::solved( ), 37 );
+    let board = Board::< 3, 3 >::from_rows( &[[o, o, o], [o, x, o], [o, o, x]] ).with_topology( Topology::Torus );
+    check_board_sampled( &board, &Goal::solved( ), 37 );
+  }
+
+  #[test]
+  fn blocked_cells_keep_their_tiles( ) {
+    let board = Board::< 2, 2 >::from_rows( &[[false, false], [false, true]] );
+    // The gap is in a blocked cell
+    assert_eq!( board.validate_rows( &[[3, 1], [2, 0]], &Goal::solved( ) ), Err( InvalidPuzzle::Unsolvable ) );
+    // A tile left the blocked cell
+    assert_eq!( board.validate_rows( &[[0, 3], [1, 2]], &Goal::solved( ) ), Err( InvalidPuzzle::Unsolvable ) );
+  }
+
+  #[test]
+  fn large_regions_with_a_cut_cell_are_undecided( ) {
+    let o = false;
+    let x = true;
+    // Two halves of 10 cells, joined by the centre
+    let board = Board::< 5, 5 >::from_rows( &[[o; 5], [o; 5], [x, x, o, x, x], [o; 5], [o; 5]] );
+    let mut rows = Goal::< 5, 5 >::solved( ).to_rows( );
+
+    // Swapping two tiles breaks the parity rule, which still applies
+    rows[ 0 ].swap( 1, 2 );
+    assert_eq!( board.validate_rows( &rows, &Goal::solved( ) ), Err( InvalidPuzzle::Unsolvable ) );
+    // Swapping two more restores the parity, but the region is too large to
+    // enumerate
+    rows[ 4 ].swap( 1, 2 );
+    assert_eq!( board.validate_rows( &rows, &Goal::solved( ) ), Err( InvalidPuzzle::Undecided ) );
+  }
+}
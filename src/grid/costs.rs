@@ -0,0 +1,87 @@
+//! Tiles with different move costs. (e.g., heavier loads) With [`TileCosts`],
+//! [`crate::solve_ita_weighted()`] minimises the total cost of the moves, with a
+//! [`crate::CostHeuristic`] such as [`crate::WeightedManhattanDistance`] or
+//! [`crate::WeightedPatternDB`]. (As tiles cost at least 1, every regular
+//! [`crate::Heuristic`] is also an admissible cost heuristic)
+
+// stdlib imports
+use std::fmt;
+// local imports
+use super::general::*;
+
+
+/// The cost of moving every tile. (e.g., the weight of a load) The search
+/// minimises the total cost of all moves. (See [`crate::solve_ita_weighted()`])
+///
+/// Invariant: Every (non-gap) tile costs at least 1. So, every admissible
+/// heuristic for unit costs remains admissible.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCosts< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// The cost of every tile. (The gap's entry is unused)
+  costs: [u8; 256],
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > TileCosts< WIDTH, HEIGHT > {
+  /// Every move costs 1. (i.e., the regular puzzle)
+  pub const fn unit( ) -> Self {
+    TileCosts { costs: [1; 256] }
+  }
+
+  /// Takes the cost of every tile, indexed by tile. (The gap's cost is
+  /// ignored) Panics if there are not `WIDTH*HEIGHT` costs, or if a tile
+  /// costs 0.
+  pub fn new( costs: &[u8] ) -> Self {
+    assert!( costs.len( ) == PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS, "there must be a cost for every tile" );
+    assert!( costs.iter( ).skip( 1 ).all( |&c| c >= 1 ), "tiles must cost at least 1" );
+
+    let mut out = [1u8; 256];
+    out[ 1..costs.len( ) ].copy_from_slice( &costs[ 1.. ] );
+    TileCosts { costs: out }
+  }
+
+  /// Returns the cost of moving the tile.
+  #[inline]
+  pub const fn cost( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> u8 {
+    self.costs[ tile.to_u8( ) as usize ]
+  }
+
+  /// Returns whether every tile costs 1.
+  pub fn is_unit( &self ) -> bool {
+    self.costs[ 1..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ].iter( ).all( |&c| c == 1 )
+  }
+
+  /// Returns the total cost of the moves on the grid, where `path` contains the
+  /// directions in which the tiles move. (As returned by the solvers) Returns
+  /// `None` if a move is impossible.
+  pub fn path_cost< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G, path: &[Dir] ) -> Option< u32 > {
+    let mut grid = grid.clone( );
+    let mut gap_cell = grid.find_gap( );
+    let mut total = 0;
+
+    for dir in path {
+      // The tile moves in `dir`, so it comes from the opposite direction
+      let tile_cell = gap_cell.step( dir.inv( ) )?;
+      let tile = grid.get_tile( tile_cell );
+      total += self.cost( tile ) as u32;
+
+      grid.set_gap_tile_unchecked( gap_cell, tile );
+      grid.clear_cell( tile_cell );
+      gap_cell = tile_cell;
+    }
+    Some( total )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Default for TileCosts< WIDTH, HEIGHT > {
+  #[inline]
+  fn default( ) -> Self {
+    TileCosts::unit( )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > fmt::Debug for TileCosts< WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    f.debug_tuple( "TileCosts" ).field( &&self.costs[ 1..num_cells ] ).finish( )
+  }
+}
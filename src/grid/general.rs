@@ -2,24 +2,30 @@
 use std::fmt;
 
 
-/// A `Puzzle24` represents a 5x5 grid of cells containing tiles [0..24]. Tile 0
-/// represents the gap.
+/// A `PuzzleGrid` represents a `WIDTH`x`HEIGHT` grid of cells containing tiles
+/// [0..WIDTH*HEIGHT-1]. Tile 0 represents the gap. By default, it is the 5x5
+/// grid of a 24-puzzle. (See also [`Puzzle24Grid`])
 /// 
 /// We represent this as a trait to allow for different internal puzzle
 /// representation (e.g., with different performance characteristics), to allow
 /// for generic algorithm implementations.
 /// 
+/// The dimensions are `const` parameters, such that the compiler specialises
+/// all algorithms for every board size. (So the 24-puzzle is as fast as when
+/// its size is hard-coded) The grid need not be square; e.g., a 3x4 grid has
+/// 3 columns and 4 rows.
+/// 
 /// 
 /// # Invariants
 /// 
 /// * Contains exactly one gap tile
-///     (note that we use this grid for complete puzzles and patterns)
-/// * All tiles are in the range [0..24]
+///   (note that we use this grid for complete puzzles and patterns)
+/// * All tiles are in the range [0..WIDTH*HEIGHT-1]
 /// 
 /// 
 /// # Example
 /// 
-/// The *solved* puzzle is as follows:
+/// The *solved* 24-puzzle is as follows:
 /// 
 /// ```ignore
 ///  0  1  2  3  4
@@ -28,34 +34,36 @@ use std::fmt;
 /// 15 16 17 18 19
 /// 20 21 22 23 24
 /// ```
-pub trait Puzzle24Grid: From< [u8; 25] > + Clone{
+pub trait PuzzleGrid< const WIDTH: usize = 5, const HEIGHT: usize = 5 >: From< [[u8; WIDTH]; HEIGHT] > + Clone {
   /// TODO
   /// 
   /// 
   /// # WARNING: `PuzzleCell` invariant
   /// 
-  /// We rely upon the invariant on `PuzzleCell`'s being in the range [0..24].
-  fn get_tile( &self, cell: PuzzleCell ) -> PuzzleTile;
+  /// We rely upon the invariant on `PuzzleCell`'s being in the range
+  /// [0..WIDTH*HEIGHT-1].
+  fn get_tile( &self, cell: PuzzleCell< WIDTH, HEIGHT > ) -> PuzzleTile< WIDTH, HEIGHT >;
 
   /// TODO
   /// 
   /// 
   /// # WARNING: `PuzzleCell` invariant
   /// 
-  /// We rely upon the invariant on `PuzzleCell`'s being in the range [0..24].
-  fn set_tile( &mut self, cell: PuzzleCell, tile: PuzzleTile );
+  /// We rely upon the invariant on `PuzzleCell`'s being in the range
+  /// [0..WIDTH*HEIGHT-1].
+  fn set_tile( &mut self, cell: PuzzleCell< WIDTH, HEIGHT >, tile: PuzzleTile< WIDTH, HEIGHT > );
 
   /// Set the a cell to be the gap tile.
   /// 
   /// # Rationale
   /// 
   /// This is equivalent to:
-  /// ```
+  /// ```ignore
   /// self.set_tile( cell, PuzzleTile::GAP );
   /// ```
   /// However, overriding this may allow for a more efficient implementation.
   #[inline]
-  fn clear_cell( &mut self, cell: PuzzleCell ) {
+  fn clear_cell( &mut self, cell: PuzzleCell< WIDTH, HEIGHT > ) {
     self.set_tile( cell, PuzzleTile::GAP );
   }
 
@@ -65,12 +73,12 @@ pub trait Puzzle24Grid: From< [u8; 25] > + Clone{
   /// # Rationale
   /// 
   /// This is equivalent to:
-  /// ```
+  /// ```ignore
   /// self.set_tile( cell, PuzzleTile::GAP );
   /// ```
   /// However, overriding this may allow for a more efficient implementation.
   #[inline]
-  fn set_gap_tile_unchecked( &mut self, gap_cell: PuzzleCell, tile: PuzzleTile ) {
+  fn set_gap_tile_unchecked( &mut self, gap_cell: PuzzleCell< WIDTH, HEIGHT >, tile: PuzzleTile< WIDTH, HEIGHT > ) {
     self.set_tile( gap_cell, tile );
   }
 
@@ -79,9 +87,9 @@ pub trait Puzzle24Grid: From< [u8; 25] > + Clone{
   /// Note that this may iterate over all cells, and thus be somewhat expensive.
   /// Often, it is better to cache the gap cell externally.
   #[inline]
-  fn find_gap( &self ) -> PuzzleCell {
-    for cell_idx in 0..25 {
-      let cell_idx = PuzzleCell::from( cell_idx );
+  fn find_gap( &self ) -> PuzzleCell< WIDTH, HEIGHT > {
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let cell_idx = PuzzleCell::from( cell_idx as u8 );
       let tile = self.get_tile( cell_idx );
   
       if tile == PuzzleTile::GAP {
@@ -91,13 +99,72 @@ pub trait Puzzle24Grid: From< [u8; 25] > + Clone{
     panic!( "Invalid puzzle (no gap)")
   }
 
+  /// Returns the solved puzzle. That is, every cell contains the tile with its
+  /// own index, so the gap is in the top-left cell.
+  #[inline]
+  fn solved( ) -> Self {
+    let mut rows = [[0u8; WIDTH]; HEIGHT];
+
+    for (cell_idx, out) in rows.as_flattened_mut( ).iter_mut( ).enumerate( ) {
+      *out = cell_idx as u8;
+    }
+    Self::from( rows )
+  }
+
+  /// Returns the tiles in all cells, by row. (The inverse of
+  /// `From< [[u8; WIDTH]; HEIGHT] >`)
+  #[inline]
+  fn to_rows( &self ) -> [[u8; WIDTH]; HEIGHT] {
+    let mut rows = [[0u8; WIDTH]; HEIGHT];
+
+    for (y, row) in rows.iter_mut( ).enumerate( ) {
+      for (x, out) in row.iter_mut( ).enumerate( ) {
+        *out = u8::from( self.get_tile( PuzzleCell::from( ( x as u8, y as u8 ) ) ) );
+      }
+    }
+    rows
+  }
+
+  /// Returns the inverse permutation of the grid, which swaps the roles of
+  /// tiles and cells. That is, tile `t` in cell `c` becomes tile `c` in cell
+  /// `t`. (So the gap moves into the cell numbered as the tile in the top-left
+  /// cell) The solved grid is its own inverse.
+  ///
+  /// When the gap is in the top-left cell, the inverse is equally far from the
+  /// solved grid. (See [`crate::DualLookup`])
+  #[inline]
+  fn inverse( &self ) -> Self {
+    let mut rows = [[0u8; WIDTH]; HEIGHT];
+    let cells = rows.as_flattened_mut( );
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let tile = self.get_tile( PuzzleCell::from( cell_idx as u8 ) );
+      cells[ u8::from( tile ) as usize ] = cell_idx as u8;
+    }
+    Self::from( rows )
+  }
+
+  /// Returns the Zobrist hash of the grid. That is, the XOR of the keys of all
+  /// tiles in their cells. (See [`PuzzleTile::zobrist_key()`]) Moving a tile
+  /// changes the hash by the XOR of its keys in both cells.
+  #[inline]
+  fn zobrist_hash( &self ) -> u64 {
+    let mut hash = 0;
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let cell = PuzzleCell::from( cell_idx as u8 );
+      hash ^= self.get_tile( cell ).zobrist_key( cell );
+    }
+    hash
+  }
+
   /// Helper to print the grid.
   #[inline]
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     write!( f, "[" )?;
 
-    for cell_idx in 0..24 {
-      let cell_idx = PuzzleCell::from( cell_idx );
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let cell_idx = PuzzleCell::from( cell_idx as u8 );
       let tile = self.get_tile( cell_idx );
 
       if tile == PuzzleTile::GAP { // it's the gap
@@ -106,119 +173,191 @@ pub trait Puzzle24Grid: From< [u8; 25] > + Clone{
         write!( f, "{}, ", u8::from( tile ) )?;
       }
     }
-
-    let final_tile = self.get_tile( PuzzleCell::from( 24 ) );
-    if final_tile == PuzzleTile::GAP { // it's the gap
-      write!( f, "_, " )?;
-    } else {
-      write!( f, "{}, ", u8::from( final_tile ) )?;
-    }
     
     write!( f, "]" )
   }
 }
 
-/// A cell inside a 24-puzzle. The cell represents the fixed location within the
-/// grid, which stores a tile.
+/// A grid of the 24-puzzle (i.e., a 5x5 [`PuzzleGrid`]), which additionally
+/// converts from and to the tiles of all 25 cells in order.
+/// 
+/// Every such grid implements this trait.
+pub trait Puzzle24Grid: PuzzleGrid + From< [u8; 25] > {
+  /// Returns the tiles in all cells. (The inverse of `From< [u8; 25] >`)
+  #[inline]
+  fn to_cells( &self ) -> [u8; 25] {
+    let mut cells = [0u8; 25];
+
+    for (cell_idx, out) in cells.iter_mut( ).enumerate( ) {
+      *out = u8::from( self.get_tile( PuzzleCell::from( cell_idx as u8 ) ) );
+    }
+    cells
+  }
+}
+
+impl< G: PuzzleGrid + From< [u8; 25] > > Puzzle24Grid for G { }
+
+/// A cell inside a puzzle grid. The cell represents the fixed location within
+/// the grid, which stores a tile.
 /// 
 /// (Note that a tile moves between cells)
 /// 
-/// Invariant: The value is in the range [0..24]
+/// Invariant: The value is in the range [0..WIDTH*HEIGHT-1]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 #[repr(transparent)]
-pub struct PuzzleCell( u8 );
+pub struct PuzzleCell< const WIDTH: usize = 5, const HEIGHT: usize = 5 >( u8 );
 
-/// A tile, contained in a cell within a 24-puzzle.
+/// A tile, contained in a cell within a puzzle grid.
 /// 
 /// (Note that a tile moves between cells)
 /// 
-/// Invariant: The value is in the range [0..24]
+/// Invariant: The value is in the range [0..WIDTH*HEIGHT-1]
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(transparent)]
-pub struct PuzzleTile( u8 );
+pub struct PuzzleTile< const WIDTH: usize = 5, const HEIGHT: usize = 5 >( u8 );
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dir {
   UP = 0, RIGHT = 1, DOWN = 2, LEFT = 3
 }
 
-impl PuzzleCell {
+impl< const WIDTH: usize, const HEIGHT: usize > PuzzleCell< WIDTH, HEIGHT > {
+  /// The number of cells in the grid.
+  pub const NUM_CELLS: usize = {
+    // Cells and tiles are stored in `u8`s
+    assert!( WIDTH >= 1 && HEIGHT >= 1 && WIDTH * HEIGHT <= 256, "unsupported grid dimensions" );
+    WIDTH * HEIGHT
+  };
+
   /// `const fn` variant of [`From`] (for `u8`)
   #[inline]
   pub const fn from_const( v: u8 ) -> Self {
-    // Check our invariant on `PuzzleTile`'s value range
-    debug_assert!( v < 25 );
+    // Check our invariant on `PuzzleCell`'s value range
+    debug_assert!( ( v as usize ) < Self::NUM_CELLS );
     PuzzleCell( v )
   }
 
+  /// `const fn` variant of [`From`] (for `u8`)
+  #[inline]
+  pub const fn to_u8( self ) -> u8 {
+    self.0
+  }
+
+  /// The column of the cell.
+  #[inline]
+  pub const fn x( &self ) -> u8 {
+    ( self.0 as usize % WIDTH ) as u8
+  }
+
+  /// The row of the cell.
+  #[inline]
+  pub const fn y( &self ) -> u8 {
+    ( self.0 as usize / WIDTH ) as u8
+  }
+
   /// Returns the `PuzzleCell` in the given direction.
   #[inline]
   pub const fn step( &self, dir: Dir ) -> Option< Self > {
-    debug_assert!( self.0 < 25 );
+    debug_assert!( ( self.0 as usize ) < Self::NUM_CELLS );
+    let v = self.0 as usize;
     match dir {
       Dir::UP =>
-        if self.0 >= 5 { Some( PuzzleCell( self.0 - 5 ) ) } else { None },
+        if v >= WIDTH { Some( PuzzleCell( ( v - WIDTH ) as u8 ) ) } else { None },
       Dir::DOWN =>
-        if self.0 < 20 { Some( PuzzleCell( self.0 + 5 ) ) } else { None },
+        if v < Self::NUM_CELLS - WIDTH { Some( PuzzleCell( ( v + WIDTH ) as u8 ) ) } else { None },
       Dir::LEFT =>
-        if self.0 % 5 >= 1 { Some( PuzzleCell( self.0 - 1 ) ) } else { None },
+        if v % WIDTH >= 1 { Some( PuzzleCell( self.0 - 1 ) ) } else { None },
       Dir::RIGHT =>
-        if self.0 % 5 <  4 { Some( PuzzleCell( self.0 + 1 ) ) } else { None },
+        if v % WIDTH < WIDTH - 1 { Some( PuzzleCell( self.0 + 1 ) ) } else { None },
     }
   }
+
+  /// Like [`PuzzleCell::step()`], but moving off an edge wraps around to the
+  /// opposite edge. (i.e., on a torus)
+  #[inline]
+  pub const fn step_wrapping( &self, dir: Dir ) -> Self {
+    debug_assert!( ( self.0 as usize ) < Self::NUM_CELLS );
+    let (x, y) = ( self.0 as usize % WIDTH, self.0 as usize / WIDTH );
+    let (x, y) =
+      match dir {
+        Dir::UP    => ( x, ( y + HEIGHT - 1 ) % HEIGHT ),
+        Dir::DOWN  => ( x, ( y + 1 ) % HEIGHT ),
+        Dir::LEFT  => ( ( x + WIDTH - 1 ) % WIDTH, y ),
+        Dir::RIGHT => ( ( x + 1 ) % WIDTH, y ),
+      };
+    PuzzleCell( ( y * WIDTH + x ) as u8 )
+  }
 }
 
-impl From< PuzzleCell > for u8 {
+impl< const WIDTH: usize, const HEIGHT: usize > From< PuzzleCell< WIDTH, HEIGHT > > for u8 {
   #[inline]
-  fn from( v: PuzzleCell ) -> Self {
+  fn from( v: PuzzleCell< WIDTH, HEIGHT > ) -> Self {
     v.0
   }
 }
 
-impl From< u8 > for PuzzleCell {
+impl< const WIDTH: usize, const HEIGHT: usize > From< u8 > for PuzzleCell< WIDTH, HEIGHT > {
   #[inline]
   fn from( v: u8 ) -> Self {
-    // Check our invariant on `PuzzleTile`'s value range
-    debug_assert!( v < 25 );
-    PuzzleCell( v )
+    PuzzleCell::from_const( v )
   }
 }
 
-impl From< (u8, u8) > for PuzzleCell {
+impl< const WIDTH: usize, const HEIGHT: usize > From< (u8, u8) > for PuzzleCell< WIDTH, HEIGHT > {
   #[inline]
   fn from( (x, y): (u8, u8) ) -> Self {
-    // Check our invariant on `PuzzleTile`'s value range
-    debug_assert!( x < 5 && y < 5 );
-    PuzzleCell( y * 5 + x )
+    // Check our invariant on `PuzzleCell`'s value range
+    debug_assert!( ( x as usize ) < WIDTH && ( y as usize ) < HEIGHT );
+    PuzzleCell::from_const( ( y as usize * WIDTH + x as usize ) as u8 )
   }
 }
 
-impl PuzzleTile {
-  pub const GAP: PuzzleTile = PuzzleTile( 0 );
+impl< const WIDTH: usize, const HEIGHT: usize > PuzzleTile< WIDTH, HEIGHT > {
+  pub const GAP: Self = PuzzleTile( 0 );
   
   /// `const fn` variant of [`From`] (for `u8`)
   #[inline]
   pub const fn from_const( v: u8 ) -> Self {
     // Check our invariant on `PuzzleTile`'s value range
-    debug_assert!( v < 25 );
+    debug_assert!( ( v as usize ) < PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS );
     PuzzleTile( v )
   }
+
+  /// `const fn` variant of [`From`] (for `u8`)
+  #[inline]
+  pub const fn to_u8( self ) -> u8 {
+    self.0
+  }
+
+  /// The random key of this tile in the cell, of which a Zobrist hash XORs
+  /// those of all tiles. (See [`PuzzleGrid::zobrist_hash()`]) The gap's key
+  /// is 0, as the other tiles determine its cell.
+  #[inline]
+  pub const fn zobrist_key( self, cell: PuzzleCell< WIDTH, HEIGHT > ) -> u64 {
+    if self.0 == 0 {
+      return 0;
+    }
+    // The SplitMix64 finaliser, which scatters the (tile, cell) pair
+    let mut z = ( ( ( self.0 as u64 ) << 8 ) | cell.0 as u64 ).wrapping_mul( 0x9E37_79B9_7F4A_7C15 );
+    z = ( z ^ ( z >> 30 ) ).wrapping_mul( 0xBF58_476D_1CE4_E5B9 );
+    z = ( z ^ ( z >> 27 ) ).wrapping_mul( 0x94D0_49BB_1331_11EB );
+    z ^ ( z >> 31 )
+  }
 }
 
-impl From< PuzzleTile > for u8 {
+impl< const WIDTH: usize, const HEIGHT: usize > From< PuzzleTile< WIDTH, HEIGHT > > for u8 {
   #[inline]
-  fn from( v: PuzzleTile ) -> Self {
+  fn from( v: PuzzleTile< WIDTH, HEIGHT > ) -> Self {
     v.0
   }
 }
 
-impl From< u8 > for PuzzleTile {
+impl< const WIDTH: usize, const HEIGHT: usize > From< u8 > for PuzzleTile< WIDTH, HEIGHT > {
   #[inline]
   fn from( v: u8 ) -> Self {
-    // Check our invariant on `PuzzleTile`'s value range
-    debug_assert!( v < 25 );
-    PuzzleTile( v )
+    PuzzleTile::from_const( v )
   }
 }
 
@@ -246,3 +385,45 @@ impl From< Dir > for char {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{Dir, PuzzleCell};
+
+  /// Checks every step from every cell against its coordinates.
+  fn check_steps< const WIDTH: usize, const HEIGHT: usize >( ) {
+    for v in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS as u8 {
+      let cell = PuzzleCell::< WIDTH, HEIGHT >::from( v );
+      let (x, y) = ( cell.x( ) as i32, cell.y( ) as i32 );
+      assert_eq!( ( y * WIDTH as i32 + x ) as u8, v );
+
+      for dir in Dir::ALL {
+        let (dx, dy) =
+          match dir {
+            Dir::UP => ( 0, -1 ),
+            Dir::RIGHT => ( 1, 0 ),
+            Dir::DOWN => ( 0, 1 ),
+            Dir::LEFT => ( -1, 0 ),
+          };
+        let (next_x, next_y) = ( x + dx, y + dy );
+        let is_inside = ( 0..WIDTH as i32 ).contains( &next_x ) && ( 0..HEIGHT as i32 ).contains( &next_y );
+        let expected = is_inside.then( || PuzzleCell::from( (next_x as u8, next_y as u8) ) );
+        assert_eq!( cell.step( dir ), expected, "{}x{}, {:?} from {:?}", WIDTH, HEIGHT, dir, cell );
+
+        let wrapped = ( next_x.rem_euclid( WIDTH as i32 ) as u8, next_y.rem_euclid( HEIGHT as i32 ) as u8 );
+        assert_eq!( cell.step_wrapping( dir ), PuzzleCell::from( wrapped ), "{}x{}, {:?} from {:?}", WIDTH, HEIGHT, dir, cell );
+      }
+    }
+  }
+
+  #[test]
+  fn steps_respect_the_edges( ) {
+    check_steps::< 2, 3 >( );
+    check_steps::< 3, 2 >( );
+    check_steps::< 4, 1 >( );
+    check_steps::< 1, 4 >( );
+    check_steps::< 3, 5 >( );
+    check_steps::< 5, 5 >( );
+    check_steps::< 16, 16 >( );
+  }
+}
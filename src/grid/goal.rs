@@ -0,0 +1,286 @@
+//! Goal arrangements, other than the solved puzzle. (e.g., [`Goal::gap_last()`],
+//! [`Goal::snake()`] or [`Goal::from_rows()`]) [`crate::solve_ita_goal()`]
+//! solves to a [`Goal`], with heuristics built for that goal. (e.g.,
+//! [`crate::ManhattanDistance::for_goal()`] or
+//! [`crate::PatternDB::build_for_goal()`]) Not every arrangement reaches every
+//! goal, which [`Goal::validate_rows()`] checks.
+//!
+//! A [`PartialGoal`] only requires some tiles to reach their cells in a goal.
+//! (e.g., `PartialGoal::first_rows( Goal::gap_last(), 2 )`) The other tiles are
+//! "don't care". [`crate::solve_ita_partial()`] stops at the first matching
+//! puzzle, with a [`crate::PatternDB::build_partial()`] whose patterns only
+//! contain the cared-about tiles.
+
+// stdlib imports
+use std::fmt;
+// local imports
+use super::general::*;
+use super::validation::{InvalidPuzzle, permutation_parity, validate_permutation, validate_solvable_to};
+
+
+/// The arrangement of tiles to which a puzzle is solved. By default, this is
+/// the solved puzzle of [`PuzzleGrid::solved()`] (with the gap in the top-left
+/// cell), but any arrangement works. (e.g., with the gap in the bottom-right
+/// cell)
+///
+/// We store the tile in every cell and the cell of every tile. (Grids have at
+/// most 256 cells; see [`PuzzleCell::NUM_CELLS`])
+///
+/// Invariant: The tiles form a permutation of [0..WIDTH*HEIGHT-1]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Goal< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  tiles: [u8; 256],
+  cells: [u8; 256],
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Goal< WIDTH, HEIGHT > {
+  /// See [`Goal::solved()`]
+  pub const SOLVED: Self = Self::solved( );
+
+  /// Every cell contains the tile with its own index. So the gap is in the
+  /// top-left cell.
+  ///
+  /// ```ignore
+  /// _ 1 2
+  /// 3 4 5
+  /// 6 7 8
+  /// ```
+  pub const fn solved( ) -> Self {
+    let mut tiles = [0u8; 256];
+
+    // Note: for-loops are disallowed in `const fn`s
+    let mut cell_idx = 0;
+    while cell_idx < PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      tiles[ cell_idx ] = cell_idx as u8;
+      cell_idx += 1;
+    }
+    Goal::from_tiles_unchecked( tiles )
+  }
+
+  /// The tiles are in order, followed by the gap in the bottom-right cell.
+  ///
+  /// ```ignore
+  /// 1 2 3
+  /// 4 5 6
+  /// 7 8 _
+  /// ```
+  pub const fn gap_last( ) -> Self {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let mut tiles = [0u8; 256];
+
+    let mut cell_idx = 0;
+    while cell_idx + 1 < num_cells {
+      tiles[ cell_idx ] = cell_idx as u8 + 1;
+      cell_idx += 1;
+    }
+    Goal::from_tiles_unchecked( tiles )
+  }
+
+  /// The tiles are in order along a "snake", which alternates between rows from
+  /// left to right and rows from right to left. It ends with the gap.
+  ///
+  /// ```ignore
+  /// 1 2 3
+  /// 6 5 4
+  /// 7 8 _
+  /// ```
+  pub const fn snake( ) -> Self {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let mut tiles = [0u8; 256];
+
+    let mut i = 0;
+    while i < num_cells {
+      let (y, x) = ( i / WIDTH, i % WIDTH );
+      let x = if y % 2 == 0 { x } else { WIDTH - 1 - x };
+      // The last cell along the snake contains the gap
+      tiles[ y * WIDTH + x ] = ( ( i + 1 ) % num_cells ) as u8;
+      i += 1;
+    }
+    Goal::from_tiles_unchecked( tiles )
+  }
+
+  /// Returns the goal with the given tiles, if they form a permutation.
+  pub const fn from_rows( rows: &[[u8; WIDTH]; HEIGHT] ) -> Result< Self, InvalidPuzzle > {
+    // Note: `?` is disallowed in `const fn`s
+    if let Err( err ) = validate_permutation( rows.as_flattened( ) ) {
+      return Err( err );
+    }
+
+    let mut tiles = [0u8; 256];
+    let mut cell_idx = 0;
+    while cell_idx < PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      tiles[ cell_idx ] = rows.as_flattened( )[ cell_idx ];
+      cell_idx += 1;
+    }
+    Ok( Goal::from_tiles_unchecked( tiles ) )
+  }
+
+  /// Returns the goal of the grid. (Assuming that it satisfies the invariants
+  /// of [`PuzzleGrid`])
+  pub fn from_grid< G: PuzzleGrid< WIDTH, HEIGHT > >( grid: &G ) -> Self {
+    let mut tiles = [0u8; 256];
+
+    for (cell_idx, &tile) in grid.to_rows( ).as_flattened( ).iter( ).enumerate( ) {
+      tiles[ cell_idx ] = tile;
+    }
+    Goal::from_tiles_unchecked( tiles )
+  }
+
+  /// Precondition: The first `WIDTH*HEIGHT` tiles form a permutation
+  const fn from_tiles_unchecked( tiles: [u8; 256] ) -> Self {
+    let mut cells = [0u8; 256];
+
+    let mut cell_idx = 0;
+    while cell_idx < PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      cells[ tiles[ cell_idx ] as usize ] = cell_idx as u8;
+      cell_idx += 1;
+    }
+    Goal { tiles, cells }
+  }
+
+  /// Returns the tile in the cell, when solved.
+  #[inline]
+  pub const fn tile( &self, cell: PuzzleCell< WIDTH, HEIGHT > ) -> PuzzleTile< WIDTH, HEIGHT > {
+    PuzzleTile::from_const( self.tiles[ cell.to_u8( ) as usize ] )
+  }
+
+  /// Returns the cell of the tile, when solved.
+  #[inline]
+  pub const fn cell( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> PuzzleCell< WIDTH, HEIGHT > {
+    PuzzleCell::from_const( self.cells[ tile.to_u8( ) as usize ] )
+  }
+
+  /// Returns the cell of the gap, when solved.
+  #[inline]
+  pub const fn gap_cell( &self ) -> PuzzleCell< WIDTH, HEIGHT > {
+    self.cell( PuzzleTile::GAP )
+  }
+
+  /// Returns the tiles in all cells, by row.
+  pub fn to_rows( &self ) -> [[u8; WIDTH]; HEIGHT] {
+    let mut rows = [[0u8; WIDTH]; HEIGHT];
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    rows.as_flattened_mut( ).copy_from_slice( &self.tiles[ ..num_cells ] );
+    rows
+  }
+
+  /// Returns the goal as a grid.
+  #[inline]
+  pub fn to_grid< G: PuzzleGrid< WIDTH, HEIGHT > >( &self ) -> G {
+    G::from( self.to_rows( ) )
+  }
+
+  /// Returns whether the grid is in this arrangement.
+  #[inline]
+  pub fn is_goal< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> bool {
+    ( 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ).all( |cell_idx| {
+      u8::from( grid.get_tile( PuzzleCell::from( cell_idx as u8 ) ) ) == self.tiles[ cell_idx ]
+    } )
+  }
+
+  /// Checks whether the rows form a puzzle which can reach this goal. (See
+  /// [`super::validate_rows()`], which checks for [`Goal::solved()`])
+  pub const fn validate_rows( &self, rows: &[[u8; WIDTH]; HEIGHT] ) -> Result< (), InvalidPuzzle > {
+    validate_solvable_to( rows.as_flattened( ), &self.cells, WIDTH )
+  }
+
+  /// Returns the parity (0 or 1) of the permutation which maps every cell to
+  /// the goal cell of its tile. The rows must contain a permutation.
+  pub(super) const fn permutation_parity( &self, rows: &[[u8; WIDTH]; HEIGHT] ) -> usize {
+    permutation_parity( rows.as_flattened( ), &self.cells )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Default for Goal< WIDTH, HEIGHT > {
+  #[inline]
+  fn default( ) -> Self {
+    Goal::SOLVED
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > fmt::Debug for Goal< WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    f.debug_tuple( "Goal" ).field( &&self.tiles[ ..num_cells ] ).finish( )
+  }
+}
+
+/// A goal where only some tiles must reach their cells in a [`Goal`]. The
+/// other tiles are "don't care", and may end up anywhere. (e.g., to solve only
+/// the first two rows) Unless the gap is cared about, it may end up anywhere
+/// too.
+///
+/// Every [`Goal`] converts into the partial goal which cares about all tiles.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PartialGoal< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: Goal< WIDTH, HEIGHT >,
+  /// Whether every tile must reach its cell
+  is_cared: [bool; 256],
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > PartialGoal< WIDTH, HEIGHT > {
+  /// Only the given tiles must reach their cells in the `goal`. (Including
+  /// the gap, if it's given) Panics if a tile is out of range.
+  pub fn new( goal: Goal< WIDTH, HEIGHT >, tiles: &[u8] ) -> Self {
+    let mut is_cared = [false; 256];
+
+    for &tile in tiles {
+      assert!( ( tile as usize ) < PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS, "tile is out of range" );
+      is_cared[ tile as usize ] = true;
+    }
+    PartialGoal { goal, is_cared }
+  }
+
+  /// Only the tiles in the first `num_rows` rows of the `goal` must reach
+  /// their cells. (Not the gap)
+  pub fn first_rows( goal: Goal< WIDTH, HEIGHT >, num_rows: usize ) -> Self {
+    let num_cells = WIDTH * num_rows.min( HEIGHT );
+    let mut is_cared = [false; 256];
+
+    for cell_idx in 0..num_cells {
+      is_cared[ u8::from( goal.tile( PuzzleCell::from( cell_idx as u8 ) ) ) as usize ] = true;
+    }
+    is_cared[ 0 ] = false;
+    PartialGoal { goal, is_cared }
+  }
+
+  /// Returns the goal, which contains the cells of the cared-about tiles.
+  #[inline]
+  pub const fn goal( &self ) -> &Goal< WIDTH, HEIGHT > {
+    &self.goal
+  }
+
+  /// Returns whether the tile must reach its cell.
+  #[inline]
+  pub const fn is_cared( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    self.is_cared[ tile.to_u8( ) as usize ]
+  }
+
+  /// Returns whether all cared-about tiles are in their cells.
+  #[inline]
+  pub fn is_goal< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> bool {
+    ( 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ).all( |tile| {
+      !self.is_cared[ tile ] || {
+        let tile = PuzzleTile::from( tile as u8 );
+        grid.get_tile( self.goal.cell( tile ) ) == tile
+      }
+    } )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > From< Goal< WIDTH, HEIGHT > > for PartialGoal< WIDTH, HEIGHT > {
+  #[inline]
+  fn from( goal: Goal< WIDTH, HEIGHT > ) -> Self {
+    PartialGoal { goal, is_cared: [true; 256] }
+  }
+}
+
+/// Shows don't-care tiles as `None`.
+impl< const WIDTH: usize, const HEIGHT: usize > fmt::Debug for PartialGoal< WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let tiles: Vec< Option< u8 > > =
+      self.goal.tiles[ ..num_cells ].iter( ).map( |&t| Some( t ).filter( |&t| self.is_cared[ t as usize ] ) ).collect( );
+    f.debug_tuple( "PartialGoal" ).field( &tiles ).finish( )
+  }
+}
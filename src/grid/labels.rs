@@ -0,0 +1,193 @@
+//! Labelled tiles, where several tiles share a label and are interchangeable.
+//! (See [`LabelGoal`]) [`LabelGoal::grid_from_labels()`] turns rows of labels
+//! into a grid, and checks solvability. [`crate::solve_ita_labels()`] stops at
+//! the first puzzle with the goal's labels, with the
+//! [`crate::MatchingDistance`], which assigns tiles with the same label to their
+//! goal cells at the minimal total distance.
+
+// stdlib imports
+use std::fmt;
+// local imports
+use super::general::*;
+use super::goal::Goal;
+use super::validation::InvalidPuzzle;
+
+
+/// A goal over tile labels, where several tiles may share a label. (e.g., three
+/// groups of colored tiles) Tiles with the same label are interchangeable, so
+/// a puzzle is solved once every cell contains a tile with the goal's label.
+/// Label 0 is the gap, which occurs exactly once.
+///
+/// Grids still contain distinct tiles. We number the goal's tiles by cell (i.e.,
+/// the gap is tile 0, and the other cells contain tiles 1, 2, ... in order),
+/// where every tile has the label of its goal cell. (See
+/// [`LabelGoal::grid_from_labels()`])
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LabelGoal< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: Goal< WIDTH, HEIGHT >,
+  /// The label of every tile
+  labels: [u8; 256],
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > LabelGoal< WIDTH, HEIGHT > {
+  /// Takes the label of every cell, by row. Label 0 (i.e., the gap) must occur
+  /// exactly once.
+  pub fn from_rows( rows: &[[u8; WIDTH]; HEIGHT] ) -> Result< Self, InvalidPuzzle > {
+    let label_rows = rows.as_flattened( );
+
+    if label_rows.iter( ).filter( |&&l| l == 0 ).count( ) != 1 {
+      return Err( InvalidPuzzle::LabelCount { label: 0 } );
+    }
+
+    let mut tile_rows = [[0u8; WIDTH]; HEIGHT];
+    let mut labels = [0u8; 256];
+    let mut next_tile = 1;
+
+    for (tile, &label) in tile_rows.as_flattened_mut( ).iter_mut( ).zip( label_rows ) {
+      if label != 0 {
+        *tile = next_tile;
+        labels[ next_tile as usize ] = label;
+        next_tile += 1;
+      }
+    }
+    // The tiles form a permutation, by construction
+    let goal = Goal::from_rows( &tile_rows )?;
+    Ok( LabelGoal { goal, labels } )
+  }
+
+  /// Returns the goal of the (distinct) tiles.
+  #[inline]
+  pub const fn goal( &self ) -> &Goal< WIDTH, HEIGHT > {
+    &self.goal
+  }
+
+  /// Returns the label of the tile.
+  #[inline]
+  pub const fn label( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> u8 {
+    self.labels[ tile.to_u8( ) as usize ]
+  }
+
+  /// Returns whether several tiles share a label.
+  pub fn has_duplicates( &self ) -> bool {
+    let mut is_seen = [false; 256];
+    self.labels[ 1..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ].iter( ).any( |&l| std::mem::replace( &mut is_seen[ l as usize ], true ) )
+  }
+
+  /// Returns whether every cell contains a tile with the goal's label.
+  #[inline]
+  pub fn is_goal< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> bool {
+    ( 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ).all( |cell_idx| {
+      let cell = PuzzleCell::from( cell_idx as u8 );
+      self.label( grid.get_tile( cell ) ) == self.label( self.goal.tile( cell ) )
+    } )
+  }
+
+  /// Returns the labels of the grid's tiles, by row.
+  pub fn to_label_rows< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> [[u8; WIDTH]; HEIGHT] {
+    let mut rows = grid.to_rows( );
+    for tile in rows.as_flattened_mut( ) {
+      *tile = self.labels[ *tile as usize ];
+    }
+    rows
+  }
+
+  /// Returns a grid with the given label in every cell, which can reach the
+  /// goal. (Tiles with the same label are assigned to cells in order)
+  ///
+  /// Every label must occur as often as in the goal. Swapping two tiles with
+  /// the same label changes the parity of the permutation. So, if any label
+  /// (except the gap) occurs more than once, either assignment is solvable.
+  /// Otherwise, the regular parity rule applies. (See
+  /// [`Goal::validate_rows()`])
+  pub fn grid_from_labels< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, rows: &[[u8; WIDTH]; HEIGHT] ) -> Result< G, InvalidPuzzle > {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let mut tile_rows = [[0u8; WIDTH]; HEIGHT];
+    let mut is_used = [false; 256];
+
+    for (tile, &label) in tile_rows.as_flattened_mut( ).iter_mut( ).zip( rows.as_flattened( ) ) {
+      let t = ( 0..num_cells ).find( |&t| !is_used[ t ] && self.labels[ t ] == label );
+      *tile = t.ok_or( InvalidPuzzle::LabelCount { label } )? as u8;
+      is_used[ *tile as usize ] = true;
+    }
+
+    if self.goal.validate_rows( &tile_rows ).is_err( ) {
+      // Swap two tiles with the same label, if any
+      let tiles = tile_rows.as_flattened_mut( );
+      let pair =
+        ( 0..num_cells ).flat_map( |i| ( i + 1..num_cells ).map( move |j| (i, j) ) )
+          .find( |&(i, j)| tiles[ i ] != 0 && self.labels[ tiles[ i ] as usize ] == self.labels[ tiles[ j ] as usize ] );
+      if let Some( (i, j) ) = pair {
+        tiles.swap( i, j );
+      }
+      self.goal.validate_rows( &tile_rows )?;
+    }
+    Ok( G::from( tile_rows ) )
+  }
+
+  /// Checks whether the labels form a puzzle which can reach the goal. (See
+  /// [`LabelGoal::grid_from_labels()`])
+  pub fn validate_labels( &self, rows: &[[u8; WIDTH]; HEIGHT] ) -> Result< (), InvalidPuzzle > {
+    self.grid_from_labels::< super::ArrayGrid< WIDTH, HEIGHT > >( rows ).map( |_| () )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > fmt::Debug for LabelGoal< WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let labels: Vec< u8 > =
+      ( 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ).map( |c| self.label( self.goal.tile( PuzzleCell::from( c as u8 ) ) ) ).collect( );
+    f.debug_tuple( "LabelGoal" ).field( &labels ).finish( )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  // stdlib imports
+  use std::collections::HashMap;
+  // local imports
+  use crate::grid::{ArrayGrid, Board, InvalidPuzzle};
+  use crate::testing::{all_grids, bfs_distances_from};
+  use super::LabelGoal;
+
+  /// Checks every arrangement of the labels against the grids which reach the
+  /// goal.
+  fn check_labels( goal: &LabelGoal< 3, 2 > ) {
+    let grids = all_grids::< 3, 2 >( );
+    let starts: Vec< ArrayGrid< 3, 2 > > = grids.iter( ).copied( ).filter( |p| goal.is_goal( p ) ).collect( );
+    let distances = bfs_distances_from( &starts, &Board::open( ) );
+
+    // Whether any grid with the labels reaches the goal
+    let mut is_solvable = HashMap::new( );
+    for p in &grids {
+      *is_solvable.entry( goal.to_label_rows( p ) ).or_insert( false ) |= distances.contains_key( p );
+    }
+
+    for (labels, is_solvable) in is_solvable {
+      match goal.grid_from_labels::< ArrayGrid< 3, 2 > >( &labels ) {
+        Ok( p ) => {
+          assert!( is_solvable && distances.contains_key( &p ), "{:?}", labels );
+          assert_eq!( goal.to_label_rows( &p ), labels );
+        },
+        Err( err ) => {
+          assert!( !is_solvable, "{:?}", labels );
+          assert_eq!( err, InvalidPuzzle::Unsolvable );
+        },
+      }
+    }
+  }
+
+  #[test]
+  fn grid_from_labels_matches_bfs( ) {
+    // Duplicate labels repair the parity
+    check_labels( &LabelGoal::from_rows( &[[0, 1, 1], [2, 2, 1]] ).unwrap( ) );
+    check_labels( &LabelGoal::from_rows( &[[1, 2, 3], [1, 2, 0]] ).unwrap( ) );
+    // Distinct labels don't
+    check_labels( &LabelGoal::from_rows( &[[0, 5, 4], [3, 2, 1]] ).unwrap( ) );
+  }
+
+  #[test]
+  fn labels_must_occur_as_often_as_in_the_goal( ) {
+    let goal = LabelGoal::from_rows( &[[0, 1, 1], [2, 2, 1]] ).unwrap( );
+    assert_eq!( goal.validate_labels( &[[1, 0, 1], [2, 2, 2]] ), Err( InvalidPuzzle::LabelCount { label: 2 } ) );
+    assert_eq!( LabelGoal::< 3, 2 >::from_rows( &[[0, 1, 1], [2, 0, 1]] ), Err( InvalidPuzzle::LabelCount { label: 0 } ) );
+  }
+}
@@ -0,0 +1,76 @@
+/// Constructs a 24-puzzle grid from a 5x5 literal, where `_` denotes the gap.
+/// Commas between cells are optional.
+///
+/// The puzzle is checked at compile time. Out-of-range tiles, duplicate tiles,
+/// and unsolvable puzzles are compilation errors (as is a wrong number of
+/// cells). By default, this produces an [`ArrayGrid24`](crate::ArrayGrid24).
+/// Another grid type (e.g., [`PackedGrid24`](crate::PackedGrid24)) can be
+/// given before a `;`. That type must be in scope, and have a `const fn
+/// from_cells( [u8; 25] )`.
+///
+/// # Example
+///
+/// ```
+/// use puzzle24::{puzzle24, ArrayGrid24, PackedGrid24};
+///
+/// const SOLVED: ArrayGrid24 =
+///   puzzle24![
+///      _  1  2  3  4
+///      5  6  7  8  9
+///     10 11 12 13 14
+///     15 16 17 18 19
+///     20 21 22 23 24
+///   ];
+///
+/// let packed =
+///   puzzle24![ PackedGrid24;
+///      1  _  2  3  4
+///      5  6  7  8  9
+///     10 11 12 13 14
+///     15 16 17 18 19
+///     20 21 22 23 24
+///   ];
+/// ```
+///
+/// Whereas, for instance, swapping two tiles is rejected:
+///
+/// ```compile_fail
+/// use puzzle24::puzzle24;
+///
+/// let unsolvable =
+///   puzzle24![
+///      _  2  1  3  4
+///      5  6  7  8  9
+///     10 11 12 13 14
+///     15 16 17 18 19
+///     20 21 22 23 24
+///   ];
+/// ```
+#[macro_export]
+macro_rules! puzzle24 {
+  // Internal rules, which translate the cells into a `[u8; 25]` literal
+  ( @cells [ $( $out:expr ),* ] ) => {
+    [ $( $out ),* ]
+  };
+  ( @cells [ $( $out:expr ),* ] , $( $rest:tt )* ) => {
+    $crate::puzzle24!( @cells [ $( $out ),* ] $( $rest )* )
+  };
+  ( @cells [ $( $out:expr ),* ] _ $( $rest:tt )* ) => {
+    $crate::puzzle24!( @cells [ $( $out, )* 0u8 ] $( $rest )* )
+  };
+  ( @cells [ $( $out:expr ),* ] $tile:literal $( $rest:tt )* ) => {
+    $crate::puzzle24!( @cells [ $( $out, )* $tile ] $( $rest )* )
+  };
+
+  // Note that the grid type is an `ident` (instead of a `ty`), as the parser
+  // would otherwise commit to parsing the first cell as a type.
+  ( $grid:ident ; $( $cell:tt )* ) => {{
+    // Evaluating it in a `const` makes validation happen at compile time
+    const GRID: $grid = $grid::from_cells( $crate::puzzle24!( @cells [ ] $( $cell )* ) );
+    GRID
+  }};
+  ( $( $cell:tt )* ) => {{
+    const GRID: $crate::ArrayGrid24 = $crate::ArrayGrid24::from_cells( $crate::puzzle24!( @cells [ ] $( $cell )* ) );
+    GRID
+  }};
+}
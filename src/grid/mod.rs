@@ -1,11 +1,30 @@
-///! A module with various representations for 24-puzzle grids.
-/// (See `Puzzle24`).
+//! A module with various representations for puzzle grids of any size. (See
+//! `PuzzleGrid`). The 24-puzzle (i.e., 5x5) is the default.
+//!
+//! Boards need not be square. For instance, `ArrayGrid::< 4, 4 >` is a
+//! 15-puzzle, and `ArrayGrid::< 5, 3 >` has three rows of five cells. The IDA*
+//! search and the heuristics are generic over the size too, so `solve_ita`
+//! solves those with the `ManhattanDistance`, or with a `PatternDB` built for
+//! their patterns. (Only the `DefaultPatternDB` is fixed to the 24-puzzle)
 
-// Contains the `Puzzle24` trait
+// Contains the `PuzzleGrid` trait
 mod general;
 mod packed_grid;
 mod array_grid;
+mod validation;
+mod goal;
+mod costs;
+mod board;
+mod labels;
+mod macros;
+#[cfg(feature = "serde")]
+mod serde_impls;
 
 pub use general::*;
 pub use packed_grid::*;
 pub use array_grid::*;
+pub use validation::*;
+pub use goal::*;
+pub use costs::*;
+pub use board::*;
+pub use labels::*;
@@ -1,17 +1,79 @@
 // local imports
 use super::general::*;
+use super::validation::{assert_valid_cells, assert_valid_rows};
 
 
-/// We pack cells as 5 bits each, requiring a total of 125 bits.
-/// 
-/// This is often slower than `ArrayGrid24` (on my machine ~30%, for some
+/// We pack cells as `BITS` bits each (i.e., just enough for the largest tile),
+/// which must fit in 128 bits. For the 24-puzzle, that is 5 bits per cell,
+/// requiring a total of 125 bits. (So the 35- and 48-puzzles do not fit)
+///
+/// This is often slower than `ArrayGrid` (on my machine ~30%, for some
 /// problems). However, it requires little space, and may be preferable when
 /// storing many states in memory (e.g., with Breadth-first search).
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
 #[repr(transparent)]
-pub struct PackedGrid24( u128 );
+pub struct PackedGrid< const WIDTH: usize = 5, const HEIGHT: usize = 5 >( u128 );
+
+/// The grid of a 24-puzzle.
+pub type PackedGrid24 = PackedGrid< 5, 5 >;
+
+impl< const WIDTH: usize, const HEIGHT: usize > PackedGrid< WIDTH, HEIGHT > {
+  /// The number of bits per cell.
+  const BITS: usize = {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    // The number of bits in the largest tile (i.e., `num_cells - 1`)
+    let bits = ( usize::BITS - ( num_cells - 1 ).leading_zeros( ) ) as usize;
+    let bits = if bits == 0 { 1 } else { bits };
+    assert!( bits * num_cells <= 128, "grid does not fit in 128 bits" );
+    bits
+  };
+
+  const MASK: u128 = ( 1 << Self::BITS ) - 1;
+
+  /// Constructs the grid, and panics if the rows do not form a solvable
+  /// puzzle. In a `const` context, that is a compilation error.
+  #[inline]
+  #[track_caller]
+  pub const fn from_rows( rows: [[u8; WIDTH]; HEIGHT] ) -> Self {
+    assert_valid_rows( &rows );
+    PackedGrid::from_rows_unchecked( rows )
+  }
+
+  /// Note that this does not validate the rows. (See
+  /// [`PackedGrid::from_rows()`])
+  #[inline]
+  pub const fn from_rows_unchecked( rows: [[u8; WIDTH]; HEIGHT] ) -> Self {
+    let mut out = 0u128;
+
+    // Note: for-loops are disallowed in `const fn`s
+    let mut y = 0;
+    while y < HEIGHT {
+      let mut x = 0;
+      while x < WIDTH {
+        let cell_idx = y * WIDTH + x;
+        out |= ( rows[ y ][ x ] as u128 ) << ( Self::BITS * cell_idx );
+        x += 1;
+      }
+      y += 1;
+    }
+
+    PackedGrid( out )
+  }
+}
 
 impl PackedGrid24 {
+  /// Constructs the grid, and panics if the cells do not form a solvable
+  /// 24-puzzle. In a `const` context, that is a compilation error. (See also
+  /// [`crate::puzzle24!`])
+  #[inline]
+  #[track_caller]
+  pub const fn from_cells( cells: [u8; 25] ) -> Self {
+    assert_valid_cells( &cells );
+    PackedGrid24::from_cells_unchecked( cells )
+  }
+
+  /// Note that this does not validate the cells. (See
+  /// [`PackedGrid24::from_cells()`])
   #[inline]
   pub const fn from_cells_unchecked( cells: [u8; 25] ) -> Self {
     let mut out = 0u128;
@@ -24,50 +86,60 @@ impl PackedGrid24 {
       cell_idx += 1;
     }
 
-    PackedGrid24( out )
+    PackedGrid( out )
   }
 }
 
-impl Puzzle24Grid for PackedGrid24 {
+impl< const WIDTH: usize, const HEIGHT: usize > PuzzleGrid< WIDTH, HEIGHT > for PackedGrid< WIDTH, HEIGHT > {
   #[inline]
-  fn get_tile( &self, cell: PuzzleCell ) -> PuzzleTile {
-    // Note that we rely upon the invariant on `PuzzleCell` being in [0..24].
-    let cell = u8::from( cell );
-    let shift = cell * 5;
-    let t = ( ( self.0 >> shift ) as u8 ) & 0b1_1111;
+  fn get_tile( &self, cell: PuzzleCell< WIDTH, HEIGHT > ) -> PuzzleTile< WIDTH, HEIGHT > {
+    // Note that we rely upon the invariant on `PuzzleCell` being in range.
+    let shift = u8::from( cell ) as usize * Self::BITS;
+    let t = ( ( self.0 >> shift ) & Self::MASK ) as u8;
     PuzzleTile::from( t )
   }
 
   #[inline]
-  fn set_tile( &mut self, cell: PuzzleCell, tile: PuzzleTile ) {
-    // Note that we rely upon the invariant on `PuzzleCell` being in [0..24].
-    let cell = u8::from( cell );
-    let shift = cell * 5;
-    let zero_mask = !( 0b1_1111u128 << shift );
+  fn set_tile( &mut self, cell: PuzzleCell< WIDTH, HEIGHT >, tile: PuzzleTile< WIDTH, HEIGHT > ) {
+    // Note that we rely upon the invariant on `PuzzleCell` being in range.
+    let shift = u8::from( cell ) as usize * Self::BITS;
+    let zero_mask = !( Self::MASK << shift );
     let new_tile_val = ( u8::from( tile ) as u128 ) << shift;
     self.0 = ( self.0 & zero_mask ) | new_tile_val;
   }
 
   #[inline]
-  fn clear_cell( &mut self, cell: PuzzleCell ) {
+  fn clear_cell( &mut self, cell: PuzzleCell< WIDTH, HEIGHT > ) {
     // Equivalent to (but more efficient):
     // self.set_tile( cell, PuzzleTile::GAP );
-    let cell = u8::from( cell );
-    let mask = !( 0b1_1111u128 << ( cell * 5 ) );
+    let shift = u8::from( cell ) as usize * Self::BITS;
+    let mask = !( Self::MASK << shift );
     self.0 &= mask;
   }
 
   #[inline]
-  fn set_gap_tile_unchecked( &mut self, gap_cell: PuzzleCell, tile: PuzzleTile ) {
+  fn set_gap_tile_unchecked( &mut self, gap_cell: PuzzleCell< WIDTH, HEIGHT >, tile: PuzzleTile< WIDTH, HEIGHT > ) {
     // Precondition: The cell `gap_cell` currently contains the gap tile.
     // Equivalent to (but more efficient):
     // self.set_tile( cell, PuzzleTile::GAP );
-    let tile = u8::from( tile );
-    let gap_cell = u8::from( gap_cell );
-    let new_tile_val = ( tile as u128 ) << ( gap_cell * 5 );
+    let shift = u8::from( gap_cell ) as usize * Self::BITS;
+    let new_tile_val = ( u8::from( tile ) as u128 ) << shift;
     // note that, if the cell does not currently contain the gap, this produces
     // arbitrary results. Hence, we strictly rely upon our precondition.
-    self.0 = self.0 | new_tile_val;
+    self.0 |= new_tile_val;
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > From< [[u8; WIDTH]; HEIGHT] > for PackedGrid< WIDTH, HEIGHT > {
+  fn from( rows: [[u8; WIDTH]; HEIGHT] ) -> Self {
+    let mut out = 0u128;
+
+    for (cell_idx, &tile) in rows.as_flattened( ).iter( ).enumerate( ) {
+      debug_assert!( ( tile as usize ) < PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ); // input validation
+      out |= ( tile as u128 ) << ( Self::BITS * cell_idx );
+    }
+
+    PackedGrid( out )
   }
 }
 
@@ -75,12 +147,11 @@ impl From< [u8; 25] > for PackedGrid24 {
   fn from( cells: [u8; 25] ) -> Self {
     let mut out = 0u128;
 
-    for cell_idx in 0..25 {
-      let tile = cells[ cell_idx ];
+    for (cell_idx, &tile) in cells.iter( ).enumerate( ) {
       debug_assert!( tile < 25 ); // input validation
       out |= ( tile as u128 ) << ( 5 * cell_idx );
     }
 
-    PackedGrid24( out )
+    PackedGrid( out )
   }
 }
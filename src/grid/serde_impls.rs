@@ -0,0 +1,107 @@
+//! `serde` support for grids. Grids are represented by their tiles (in cell
+//! order), where `0` is the gap. (i.e., 25 tiles for the 24-puzzle)
+//! Deserialisation checks the grid invariants, so it rejects out-of-range and
+//! duplicate tiles. (See [`PuzzleGrid`])
+
+// stdlib imports
+use std::fmt;
+use std::marker::PhantomData;
+// external imports
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+// local imports
+use super::general::*;
+use super::array_grid::ArrayGrid;
+use super::packed_grid::PackedGrid;
+use super::validation::validate_row_tiles;
+
+
+/// Serialises the tiles of a grid as a tuple. (Like `[u8; 25]` would be, for
+/// the 24-puzzle)
+fn serialize_cells< S: Serializer, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( grid: &G, serializer: S ) -> Result< S::Ok, S::Error > {
+  let mut tuple = serializer.serialize_tuple( WIDTH * HEIGHT )?;
+  for tile in grid.to_rows( ).as_flattened( ) {
+    tuple.serialize_element( tile )?;
+  }
+  tuple.end( )
+}
+
+/// Deserialises the tiles of a grid, and checks that they form a permutation.
+fn deserialize_cells< 'de, D: Deserializer< 'de >, const WIDTH: usize, const HEIGHT: usize >( deserializer: D ) -> Result< [[u8; WIDTH]; HEIGHT], D::Error > {
+  let rows = deserializer.deserialize_tuple( WIDTH * HEIGHT, CellsVisitor( PhantomData ) )?;
+  validate_row_tiles( &rows ).map_err( D::Error::custom )?;
+  Ok( rows )
+}
+
+struct CellsVisitor< const WIDTH: usize, const HEIGHT: usize >( PhantomData< [[u8; WIDTH]; HEIGHT] > );
+
+impl< 'de, const WIDTH: usize, const HEIGHT: usize > Visitor< 'de > for CellsVisitor< WIDTH, HEIGHT > {
+  type Value = [[u8; WIDTH]; HEIGHT];
+
+  fn expecting( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+    write!( f, "{} tiles", WIDTH * HEIGHT )
+  }
+
+  fn visit_seq< A: SeqAccess< 'de > >( self, mut seq: A ) -> Result< Self::Value, A::Error > {
+    let mut rows = [[0u8; WIDTH]; HEIGHT];
+    for (i, tile) in rows.as_flattened_mut( ).iter_mut( ).enumerate( ) {
+      *tile = seq.next_element( )?.ok_or_else( || A::Error::invalid_length( i, &self ) )?;
+    }
+    Ok( rows )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Serialize for ArrayGrid< WIDTH, HEIGHT > {
+  fn serialize< S: Serializer >( &self, serializer: S ) -> Result< S::Ok, S::Error > {
+    serialize_cells( self, serializer )
+  }
+}
+
+impl< 'de, const WIDTH: usize, const HEIGHT: usize > Deserialize< 'de > for ArrayGrid< WIDTH, HEIGHT > {
+  fn deserialize< D: Deserializer< 'de > >( deserializer: D ) -> Result< Self, D::Error > {
+    deserialize_cells( deserializer ).map( ArrayGrid::from )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Serialize for PackedGrid< WIDTH, HEIGHT > {
+  fn serialize< S: Serializer >( &self, serializer: S ) -> Result< S::Ok, S::Error > {
+    serialize_cells( self, serializer )
+  }
+}
+
+impl< 'de, const WIDTH: usize, const HEIGHT: usize > Deserialize< 'de > for PackedGrid< WIDTH, HEIGHT > {
+  fn deserialize< D: Deserializer< 'de > >( deserializer: D ) -> Result< Self, D::Error > {
+    deserialize_cells( deserializer ).map( PackedGrid::from )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Serialize for PuzzleCell< WIDTH, HEIGHT > {
+  fn serialize< S: Serializer >( &self, serializer: S ) -> Result< S::Ok, S::Error > {
+    u8::from( *self ).serialize( serializer )
+  }
+}
+
+impl< 'de, const WIDTH: usize, const HEIGHT: usize > Deserialize< 'de > for PuzzleCell< WIDTH, HEIGHT > {
+  fn deserialize< D: Deserializer< 'de > >( deserializer: D ) -> Result< Self, D::Error > {
+    match u8::deserialize( deserializer )? {
+      v if ( v as usize ) < WIDTH * HEIGHT => Ok( PuzzleCell::from( v ) ),
+      v => Err( D::Error::custom( format!( "cell {} is out of range", v ) ) ),
+    }
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Serialize for PuzzleTile< WIDTH, HEIGHT > {
+  fn serialize< S: Serializer >( &self, serializer: S ) -> Result< S::Ok, S::Error > {
+    u8::from( *self ).serialize( serializer )
+  }
+}
+
+impl< 'de, const WIDTH: usize, const HEIGHT: usize > Deserialize< 'de > for PuzzleTile< WIDTH, HEIGHT > {
+  fn deserialize< D: Deserializer< 'de > >( deserializer: D ) -> Result< Self, D::Error > {
+    match u8::deserialize( deserializer )? {
+      v if ( v as usize ) < WIDTH * HEIGHT => Ok( PuzzleTile::from( v ) ),
+      v => Err( D::Error::custom( format!( "tile {} is out of range", v ) ) ),
+    }
+  }
+}
@@ -0,0 +1,252 @@
+// stdlib imports
+use std::fmt;
+
+
+/// The reason why some cells do not form a valid (and solvable) puzzle. (See
+/// [`validate_cells()`] and [`validate_rows()`])
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidPuzzle {
+  /// The cell contains a tile outside the range [0..WIDTH*HEIGHT-1]
+  OutOfRange { cell: u8, tile: u8 },
+  /// The tile occurs in more than one cell
+  Duplicate { tile: u8 },
+  /// The solved puzzle is unreachable from the puzzle
+  Unsolvable,
+  /// The puzzle contains a different number of tiles with the label than its
+  /// goal. (See [`crate::LabelGoal`])
+  LabelCount { label: u8 },
+  /// The board's region is too large to decide whether the puzzle is
+  /// solvable, though no rule rules it out. (See [`crate::Board::validate_rows()`])
+  Undecided,
+}
+
+impl fmt::Display for InvalidPuzzle {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      InvalidPuzzle::OutOfRange { cell, tile } =>
+        write!( f, "tile {} in cell {} is out of range", tile, cell ),
+      InvalidPuzzle::Duplicate { tile } =>
+        write!( f, "tile {} occurs more than once", tile ),
+      InvalidPuzzle::Unsolvable =>
+        write!( f, "puzzle is unsolvable" ),
+      InvalidPuzzle::LabelCount { label } =>
+        write!( f, "label {} occurs a different number of times than in the goal", label ),
+      InvalidPuzzle::Undecided =>
+        write!( f, "puzzle is too large to decide whether it is solvable" ),
+    }
+  }
+}
+
+impl std::error::Error for InvalidPuzzle { }
+
+/// Panics unless the cells form a solvable 24-puzzle. (See [`validate_cells()`])
+///
+/// When evaluated at compile time (e.g., in a `const`), this rejects invalid
+/// puzzles with a compilation error. Note that formatting is disallowed in
+/// `const fn`s, so the messages are fixed.
+#[track_caller]
+pub const fn assert_valid_cells( cells: &[u8; 25] ) {
+  assert_valid( validate_cells( cells ) )
+}
+
+/// Panics unless the rows form a solvable puzzle. (See [`validate_rows()`] and
+/// [`assert_valid_cells()`])
+#[track_caller]
+pub const fn assert_valid_rows< const WIDTH: usize, const HEIGHT: usize >( rows: &[[u8; WIDTH]; HEIGHT] ) {
+  assert_valid( validate_rows( rows ) )
+}
+
+#[track_caller]
+const fn assert_valid( result: Result< (), InvalidPuzzle > ) {
+  match result {
+    Ok( () ) => { },
+    Err( InvalidPuzzle::OutOfRange { .. } ) => panic!( "puzzle contains an out-of-range tile" ),
+    Err( InvalidPuzzle::Duplicate { .. } ) => panic!( "puzzle contains a duplicate tile" ),
+    Err( InvalidPuzzle::Unsolvable ) => panic!( "puzzle is unsolvable" ),
+    Err( InvalidPuzzle::LabelCount { .. } ) => panic!( "puzzle contains a label too often or too rarely" ),
+    Err( InvalidPuzzle::Undecided ) => panic!( "puzzle may be unsolvable" ),
+  }
+}
+
+/// Checks whether the cells contain every tile in [0..24] exactly once. (See
+/// [`crate::PuzzleGrid`]) Note that this does *not* check solvability. (See
+/// [`validate_cells()`])
+pub const fn validate_tiles( cells: &[u8; 25] ) -> Result< (), InvalidPuzzle > {
+  validate_permutation( cells )
+}
+
+/// Checks whether the cells form a 24-puzzle which can reach the solved
+/// puzzle. (See [`crate::PuzzleGrid`])
+pub const fn validate_cells( cells: &[u8; 25] ) -> Result< (), InvalidPuzzle > {
+  validate_solvable( cells, 5 )
+}
+
+/// Like [`validate_tiles()`], but for a grid of any size. (The tiles are in the
+/// range [0..WIDTH*HEIGHT-1])
+pub const fn validate_row_tiles< const WIDTH: usize, const HEIGHT: usize >( rows: &[[u8; WIDTH]; HEIGHT] ) -> Result< (), InvalidPuzzle > {
+  validate_permutation( rows.as_flattened( ) )
+}
+
+/// Like [`validate_cells()`], but for a grid of any size.
+pub const fn validate_rows< const WIDTH: usize, const HEIGHT: usize >( rows: &[[u8; WIDTH]; HEIGHT] ) -> Result< (), InvalidPuzzle > {
+  validate_solvable( rows.as_flattened( ), WIDTH )
+}
+
+/// Checks whether the cells contain every tile in [0..cells.len( )-1] exactly
+/// once. Grids have at most 256 cells. (See [`crate::PuzzleCell::NUM_CELLS`])
+pub(super) const fn validate_permutation( cells: &[u8] ) -> Result< (), InvalidPuzzle > {
+  let mut is_seen = [false; 256];
+
+  // Note: for-loops are disallowed in `const fn`s
+  let mut cell_idx = 0;
+  while cell_idx < cells.len( ) {
+    let tile = cells[ cell_idx ];
+
+    if tile as usize >= cells.len( ) {
+      return Err( InvalidPuzzle::OutOfRange { cell: cell_idx as u8, tile } );
+    } else if is_seen[ tile as usize ] {
+      return Err( InvalidPuzzle::Duplicate { tile } );
+    }
+    is_seen[ tile as usize ] = true;
+    cell_idx += 1;
+  }
+
+  Ok( () )
+}
+
+/// Checks whether the cells (with rows of `width` cells) form a puzzle which
+/// can reach the solved puzzle.
+const fn validate_solvable( cells: &[u8], width: usize ) -> Result< (), InvalidPuzzle > {
+  // For the solved puzzle, tiles are identical to cells
+  let mut goal_cells = [0u8; 256];
+
+  // Note: for-loops are disallowed in `const fn`s
+  let mut tile = 0;
+  while tile < 256 {
+    goal_cells[ tile ] = tile as u8;
+    tile += 1;
+  }
+  validate_solvable_to( cells, &goal_cells, width )
+}
+
+/// Checks whether the cells (with rows of `width` cells) form a puzzle which
+/// can reach the goal, where `goal_cells` contains the goal cell of every tile.
+/// (See [`crate::Goal`])
+///
+/// A puzzle is solvable iff the parity of its permutation (including the gap)
+/// equals the parity of the gap's Manhattan distance to its solved cell. Every
+/// move swaps the gap with a tile (changing the permutation's parity) and moves
+/// the gap by one cell.
+///
+/// This holds for any rectangle of at least two rows and columns. For the
+/// solved puzzle, it is equivalent to the common rule on the number of
+/// inversions (i.e., pairs of non-gap tiles in the wrong order, read by row),
+/// which differs by width: A vertical move jumps a tile over `width - 1`
+/// others. So, for odd widths, the number of inversions must be even. For even
+/// widths, the number of inversions plus the gap's row must be even.
+///
+/// On a single row or column, tiles never pass each other. So, the order of the
+/// non-gap tiles must match the goal's. (Then, the gap reaches every cell)
+pub(super) const fn validate_solvable_to( cells: &[u8], goal_cells: &[u8; 256], width: usize ) -> Result< (), InvalidPuzzle > {
+  // Note: `?` is disallowed in `const fn`s
+  if let Err( err ) = validate_permutation( cells ) {
+    return Err( err );
+  }
+
+  if width == 1 || width == cells.len( ) {
+    // The goal cells of the non-gap tiles must increase
+    let mut next_goal_cell = 0;
+    let mut cell_idx = 0;
+    while cell_idx < cells.len( ) {
+      if cells[ cell_idx ] != 0 {
+        let goal_cell = goal_cells[ cells[ cell_idx ] as usize ] as usize;
+        if goal_cell < next_goal_cell {
+          return Err( InvalidPuzzle::Unsolvable );
+        }
+        next_goal_cell = goal_cell + 1;
+      }
+      cell_idx += 1;
+    }
+    return Ok( () );
+  }
+
+  let mut gap_cell = 0;
+  while cells[ gap_cell ] != 0 {
+    gap_cell += 1;
+  }
+  let permutation_parity = permutation_parity( cells, goal_cells );
+
+  let goal_gap_cell = goal_cells[ 0 ] as usize;
+  let gap_distance = ( gap_cell % width ).abs_diff( goal_gap_cell % width ) + ( gap_cell / width ).abs_diff( goal_gap_cell / width );
+
+  if permutation_parity == gap_distance % 2 {
+    Ok( () )
+  } else {
+    Err( InvalidPuzzle::Unsolvable )
+  }
+}
+
+/// Returns the parity (0 or 1) of the permutation which maps every cell to the
+/// goal cell of its tile, where `goal_cells` contains the goal cell of every
+/// tile. The cells must contain a permutation.
+pub(super) const fn permutation_parity( cells: &[u8], goal_cells: &[u8; 256] ) -> usize {
+  // A permutation with `n` elements and `c` cycles is the composition of
+  // `n - c` swaps.
+  let mut is_visited = [false; 256];
+  let mut num_cycles = 0;
+  let mut cell_idx = 0;
+  while cell_idx < cells.len( ) {
+    if !is_visited[ cell_idx ] {
+      num_cycles += 1;
+
+      let mut c = cell_idx;
+      while !is_visited[ c ] {
+        is_visited[ c ] = true;
+        c = goal_cells[ cells[ c ] as usize ] as usize;
+      }
+    }
+    cell_idx += 1;
+  }
+  ( cells.len( ) - num_cycles ) % 2
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{ArrayGrid, Board, Goal};
+  use crate::testing::{bfs_distances, permutations};
+  use super::{InvalidPuzzle, validate_rows};
+
+  /// Checks every arrangement against the grids which reach the goal.
+  fn check_solvability< const WIDTH: usize, const HEIGHT: usize >( goal: &Goal< WIDTH, HEIGHT > ) {
+    let distances = bfs_distances( goal, &Board::open( ) );
+
+    for tiles in permutations( WIDTH * HEIGHT ) {
+      let mut rows = [[0; WIDTH]; HEIGHT];
+      rows.as_flattened_mut( ).copy_from_slice( &tiles );
+      let is_reachable = distances.contains_key( &ArrayGrid::from( rows ) );
+
+      assert_eq!( goal.validate_rows( &rows ).is_ok( ), is_reachable, "{:?}", rows );
+      if *goal == Goal::solved( ) {
+        assert_eq!( validate_rows( &rows ).is_ok( ), is_reachable, "{:?}", rows );
+      }
+    }
+  }
+
+  #[test]
+  fn solvability_matches_bfs( ) {
+    check_solvability( &Goal::< 4, 1 >::solved( ) );
+    check_solvability( &Goal::< 1, 4 >::solved( ) );
+    check_solvability( &Goal::< 5, 1 >::gap_last( ) );
+    check_solvability( &Goal::< 1, 5 >::from_rows( &[[2], [0], [4], [1], [3]] ).unwrap( ) );
+    check_solvability( &Goal::< 2, 2 >::solved( ) );
+    check_solvability( &Goal::< 2, 3 >::solved( ) );
+    check_solvability( &Goal::< 3, 2 >::snake( ) );
+  }
+
+  #[test]
+  fn tiles_on_a_line_never_pass_each_other( ) {
+    // It satisfies the parity rule
+    assert_eq!( validate_rows( &[[3, 0, 1, 2]] ), Err( InvalidPuzzle::Unsolvable ) );
+    assert_eq!( validate_rows( &[[1], [2], [0], [3]] ), Ok( () ) );
+  }
+}
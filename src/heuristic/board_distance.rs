@@ -0,0 +1,75 @@
+// local imports
+use crate::grid::{Board, Dir, Goal, PuzzleCell, PuzzleGrid, PuzzleTile};
+use super::general::Heuristic;
+
+
+/// Like [`crate::ManhattanDistance`], but on a board with blocked cells, where
+/// tiles take detours around them. So, we sum the lengths of the shortest paths
+/// (through open cells) of all tiles to their goal cells. Every move brings
+/// only one tile one step closer, so this is admissible. (On a torus, paths
+/// wrap around the edges)
+/// 
+/// Tiles which cannot reach their goal cell count as 255. (Such puzzles are
+/// unsolvable; see [`Board::validate_rows()`]) Like [`crate::ManhattanDistance`],
+/// the sum saturates at 255.
+#[derive(Clone, Debug)]
+pub struct BoardDistance< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// The distance of every tile in every cell, indexed by `tile * WIDTH*HEIGHT
+  /// + cell`.
+  distances: Vec< u8 >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > BoardDistance< WIDTH, HEIGHT > {
+  /// Computes the distances to the goal cells on the board, with a
+  /// breadth-first search for every tile.
+  pub fn new( board: &Board< WIDTH, HEIGHT >, goal: &Goal< WIDTH, HEIGHT > ) -> Self {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let mut distances: Vec< u8 > = vec![ 0xFF; num_cells * num_cells ];
+
+    for tile in 1..num_cells {
+      let goal_cell = goal.cell( PuzzleTile::from( tile as u8 ) );
+      let tile_distances = &mut distances[ tile * num_cells..( tile + 1 ) * num_cells ];
+      tile_distances[ u8::from( goal_cell ) as usize ] = 0;
+
+      // A tile in a blocked cell never moves, so only its goal cell counts
+      if board.is_blocked( goal_cell ) {
+        continue;
+      }
+
+      let mut queue = vec![ goal_cell ];
+      let mut i = 0;
+      while i < queue.len( ) {
+        let cell = queue[ i ];
+        let dist = tile_distances[ u8::from( cell ) as usize ];
+
+        for dir in Dir::ALL {
+          if let Some( adj_cell ) = board.step( cell, dir ) {
+            if tile_distances[ u8::from( adj_cell ) as usize ] == 0xFF && adj_cell != goal_cell {
+              tile_distances[ u8::from( adj_cell ) as usize ] = dist.saturating_add( 1 );
+              queue.push( adj_cell );
+            }
+          }
+        }
+        i += 1;
+      }
+    }
+    BoardDistance { distances }
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for BoardDistance< WIDTH, HEIGHT > {
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let mut sum = 0u8;
+
+    for cell_idx in 0..num_cells {
+      let tile = p.get_tile( PuzzleCell::from( cell_idx as u8 ) );
+
+      if tile != PuzzleTile::GAP {
+        sum = sum.saturating_add( self.distances[ u8::from( tile ) as usize * num_cells + cell_idx ] );
+      }
+    }
+    sum
+  }
+}
@@ -0,0 +1,391 @@
+// stdlib imports
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+// local imports
+use crate::grid::{PuzzleCell, PuzzleGrid, PuzzleTile};
+use super::general::{DisjointHeuristic, Heuristic};
+
+
+/// The maximum of two heuristics, which is admissible when both are. (And
+/// consistent when both are)
+///
+/// Like every combinator, it is itself a [`Heuristic`], so it nests. (e.g.,
+/// `Max( Max( a, b ), c )`) Combined heuristics may also be borrowed, such that
+/// large tables are shared:
+///
+/// ```
+/// use puzzle24::{ArrayGrid, DualLookup, Heuristic, Max, PatternDB, solve_ita};
+///
+/// let db = PatternDB::< 3, 3 >::build( &[ &[1,2,3,4], &[5,6,7,8] ] );
+/// let h = Max( &db, DualLookup( &db ) );
+///
+/// let p = ArrayGrid::from_rows( [[0,2,1], [3,4,5], [6,8,7]] );
+/// assert!( h.compute( &p ) >= db.compute( &p ) );
+/// assert_eq!( solve_ita( &h, p ).1.len( ), solve_ita( &db, p ).1.len( ) );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Max< A, B >( pub A, pub B );
+
+impl< A, B, const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for Max< A, B >
+  where A: Heuristic< WIDTH, HEIGHT >, B: Heuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    self.0.compute( p ).max( self.1.compute( p ) )
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    self.0.is_consistent( ) && self.1.is_consistent( )
+  }
+}
+
+/// Counts the moves of the tiles of either.
+impl< A, B, const WIDTH: usize, const HEIGHT: usize > DisjointHeuristic< WIDTH, HEIGHT > for Max< A, B >
+  where A: DisjointHeuristic< WIDTH, HEIGHT >, B: DisjointHeuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn counts_tile( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    self.0.counts_tile( tile ) || self.1.counts_tile( tile )
+  }
+}
+
+/// The sum of two heuristics, which count the moves of disjoint tiles. (See
+/// [`DisjointHeuristic`]) No move is counted twice, so the sum is admissible.
+/// A move changes at most one of them, so it is consistent when both are.
+///
+/// The sum saturates at 255. That remains admissible.
+#[derive(Clone, Copy, Debug)]
+pub struct AdditiveSum< A, B > {
+  a: A,
+  b: B,
+}
+
+impl< A, B > AdditiveSum< A, B > {
+  /// Panics if both heuristics count the moves of some tile.
+  pub fn new< const WIDTH: usize, const HEIGHT: usize >( a: A, b: B ) -> Self
+    where A: DisjointHeuristic< WIDTH, HEIGHT >, B: DisjointHeuristic< WIDTH, HEIGHT >
+  {
+    let is_disjoint =
+      ( 1..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS ).all( |t| {
+        let tile = PuzzleTile::from( t as u8 );
+        !( a.counts_tile( tile ) && b.counts_tile( tile ) )
+      } );
+    assert!( is_disjoint, "the heuristics count the moves of the same tiles" );
+    AdditiveSum { a, b }
+  }
+
+  #[inline]
+  pub fn parts( &self ) -> (&A, &B) {
+    (&self.a, &self.b)
+  }
+}
+
+impl< A, B, const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for AdditiveSum< A, B >
+  where A: DisjointHeuristic< WIDTH, HEIGHT >, B: DisjointHeuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    self.a.compute( p ).saturating_add( self.b.compute( p ) )
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    self.a.is_consistent( ) && self.b.is_consistent( )
+  }
+}
+
+/// Counts the moves of the tiles of both.
+impl< A, B, const WIDTH: usize, const HEIGHT: usize > DisjointHeuristic< WIDTH, HEIGHT > for AdditiveSum< A, B >
+  where A: DisjointHeuristic< WIDTH, HEIGHT >, B: DisjointHeuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn counts_tile( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    self.a.counts_tile( tile ) || self.b.counts_tile( tile )
+  }
+}
+
+/// Remembers the values of the wrapped heuristic for the most recently computed
+/// grids, identified by their Zobrist hashes. (See
+/// [`PuzzleGrid::zobrist_hash()`]) Once full, it forgets the least recently
+/// used grid.
+///
+/// Heuristics are not passed the search's incremental hash (see
+/// [`crate::Puzzle::zobrist_hash()`]), so every lookup hashes the grid anew,
+/// which visits all its cells. So this only pays off for expensive heuristics.
+/// (e.g., those which look up many tables, or search) It assumes that distinct
+/// grids (which it remembers at once) never collide.
+///
+/// Threads share the cache behind a lock, which every lookup takes. (e.g., the
+/// workers of [`crate::service::serve()`])
+#[derive(Debug)]
+pub struct Cached< H > {
+  h: H,
+  cache: Mutex< Lru >,
+}
+
+impl< H > Cached< H > {
+  /// Remembers at most `capacity` grids. (But at least one)
+  pub fn new( h: H, capacity: usize ) -> Self {
+    Cached { h, cache: Mutex::new( Lru::new( capacity.max( 1 ) ) ) }
+  }
+
+  #[inline]
+  pub fn inner( &self ) -> &H {
+    &self.h
+  }
+
+  /// Returns the number of lookups which found their grid, and of those which
+  /// did not.
+  pub fn num_hits_misses( &self ) -> (usize, usize) {
+    let cache = self.lock( );
+    (cache.num_hits, cache.num_misses)
+  }
+
+  /// Forgets all grids.
+  pub fn clear( &self ) {
+    self.lock( ).clear( );
+  }
+
+  /// Locks the cache. (A panicking thread leaves it consistent, as the wrapped
+  /// heuristic runs without the lock)
+  #[inline]
+  fn lock( &self ) -> MutexGuard< '_, Lru > {
+    self.cache.lock( ).unwrap_or_else( PoisonError::into_inner )
+  }
+}
+
+impl< H: Clone > Clone for Cached< H > {
+  fn clone( &self ) -> Self {
+    Cached { h: self.h.clone( ), cache: Mutex::new( self.lock( ).clone( ) ) }
+  }
+}
+
+impl< H, const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for Cached< H >
+  where H: Heuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    let hash = p.zobrist_hash( );
+    if let Some( hx ) = self.lock( ).get( hash ) {
+      return hx;
+    }
+    let hx = self.h.compute( p );
+    self.lock( ).insert( hash, hx );
+    hx
+  }
+
+  /// On a miss, the wrapped heuristic still updates incrementally.
+  #[inline]
+  fn compute_moved< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P, hx: u8, tile: PuzzleTile< WIDTH, HEIGHT >, from_cell: PuzzleCell< WIDTH, HEIGHT >, to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u8 {
+    let hash = p.zobrist_hash( );
+    if let Some( hx ) = self.lock( ).get( hash ) {
+      return hx;
+    }
+    let hx = self.h.compute_moved( p, hx, tile, from_cell, to_cell );
+    self.lock( ).insert( hash, hx );
+    hx
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    self.h.is_consistent( )
+  }
+}
+
+impl< H, const WIDTH: usize, const HEIGHT: usize > DisjointHeuristic< WIDTH, HEIGHT > for Cached< H >
+  where H: DisjointHeuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn counts_tile( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    self.h.counts_tile( tile )
+  }
+}
+
+/// A least-recently-used cache of heuristic values. Its entries form a doubly
+/// linked list (by their indices), from the most to the least recently used.
+#[derive(Clone, Debug)]
+struct Lru {
+  capacity: usize,
+  entries: Vec< LruEntry >,
+  /// The index of every hash's entry
+  indices: HashMap< u64, u32 >,
+  /// The most recently used entry (or [`Lru::NONE`])
+  head: u32,
+  /// The least recently used entry (or [`Lru::NONE`])
+  tail: u32,
+  num_hits: usize,
+  num_misses: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct LruEntry {
+  hash: u64,
+  hx: u8,
+  prev: u32,
+  next: u32,
+}
+
+impl Lru {
+  const NONE: u32 = u32::MAX;
+
+  fn new( capacity: usize ) -> Self {
+    // Indices must remain below `Lru::NONE`
+    let capacity = capacity.min( Lru::NONE as usize );
+    Lru {
+      capacity,
+      entries: Vec::new( ),
+      indices: HashMap::new( ),
+      head: Lru::NONE,
+      tail: Lru::NONE,
+      num_hits: 0,
+      num_misses: 0,
+    }
+  }
+
+  fn clear( &mut self ) {
+    self.entries.clear( );
+    self.indices.clear( );
+    self.head = Lru::NONE;
+    self.tail = Lru::NONE;
+  }
+
+  /// Returns the value of the hash, which becomes the most recently used.
+  fn get( &mut self, hash: u64 ) -> Option< u8 > {
+    let Some( &idx ) = self.indices.get( &hash ) else {
+      self.num_misses += 1;
+      return None;
+    };
+    self.num_hits += 1;
+    self.unlink( idx );
+    self.link_front( idx );
+    Some( self.entries[ idx as usize ].hx )
+  }
+
+  /// Inserts a hash, which replaces the least recently used when full. (Another
+  /// thread may have inserted it since it was missed, with the same value)
+  fn insert( &mut self, hash: u64, hx: u8 ) {
+    let idx =
+      if let Some( &idx ) = self.indices.get( &hash ) {
+        self.unlink( idx );
+        self.entries[ idx as usize ].hx = hx;
+        idx
+      } else if self.entries.len( ) < self.capacity {
+        self.entries.push( LruEntry { hash, hx, prev: Lru::NONE, next: Lru::NONE } );
+        ( self.entries.len( ) - 1 ) as u32
+      } else {
+        let idx = self.tail;
+        self.unlink( idx );
+        self.indices.remove( &self.entries[ idx as usize ].hash );
+        self.entries[ idx as usize ] = LruEntry { hash, hx, prev: Lru::NONE, next: Lru::NONE };
+        idx
+      };
+    self.indices.insert( hash, idx );
+    self.link_front( idx );
+  }
+
+  fn unlink( &mut self, idx: u32 ) {
+    let LruEntry { prev, next, .. } = self.entries[ idx as usize ];
+
+    if prev == Lru::NONE {
+      self.head = next;
+    } else {
+      self.entries[ prev as usize ].next = next;
+    }
+    if next == Lru::NONE {
+      self.tail = prev;
+    } else {
+      self.entries[ next as usize ].prev = prev;
+    }
+  }
+
+  fn link_front( &mut self, idx: u32 ) {
+    let head = self.head;
+    let entry = &mut self.entries[ idx as usize ];
+    entry.prev = Lru::NONE;
+    entry.next = head;
+
+    if head == Lru::NONE {
+      self.tail = idx;
+    } else {
+      self.entries[ head as usize ].prev = idx;
+    }
+    self.head = idx;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{ArrayGrid, Goal};
+  use crate::heuristic::{Heuristic, ManhattanDistance, PatternDB};
+  use crate::search::solve_ita;
+  use crate::testing::random_grids;
+  use super::{AdditiveSum, Cached, Lru};
+
+  #[test]
+  fn lru_forgets_the_least_recently_used( ) {
+    let mut lru = Lru::new( 2 );
+    lru.insert( 1, 10 );
+    lru.insert( 2, 20 );
+    assert_eq!( lru.get( 1 ), Some( 10 ) );
+    // Replaces 2, as 1 was used since
+    lru.insert( 3, 30 );
+    assert_eq!( lru.get( 2 ), None );
+    assert_eq!( lru.get( 3 ), Some( 30 ) );
+    // Replaces 1
+    lru.insert( 2, 20 );
+    assert_eq!( lru.get( 1 ), None );
+    assert_eq!( (lru.get( 2 ), lru.get( 3 )), (Some( 20 ), Some( 30 )) );
+    // Inserting a remembered hash does not replace another one
+    lru.insert( 2, 20 );
+    assert_eq!( (lru.get( 2 ), lru.get( 3 )), (Some( 20 ), Some( 30 )) );
+    assert_eq!( lru.entries.len( ), 2 );
+    assert_eq!( (lru.num_hits, lru.num_misses), (6, 2) );
+
+    lru.clear( );
+    assert_eq!( lru.get( 3 ), None );
+  }
+
+  #[test]
+  fn cached_heuristics_count_hits_and_misses( ) {
+    let h = ManhattanDistance::< 3, 3 >::new( );
+    let cached = Cached::new( h, 2 );
+    let grids = random_grids( &Goal::< 3, 3 >::solved( ), 3, 20, 6 );
+    let (a, b, c) = (grids[ 0 ], grids[ 1 ], grids[ 2 ]);
+
+    for p in [ a, b, a, c, b, c ] {
+      assert_eq!( cached.compute( &p ), h.compute( &p ) );
+    }
+    // Only `a` and `c` were remembered when they were looked up again
+    assert_eq!( cached.num_hits_misses( ), (2, 4) );
+
+    cached.clear( );
+    assert_eq!( cached.compute( &c ), h.compute( &c ) );
+    assert_eq!( cached.num_hits_misses( ), (2, 5) );
+  }
+
+  #[test]
+  fn cached_heuristics_are_shared_between_threads( ) {
+    let db = PatternDB::< 3, 3 >::build( &[&[1,2,3,4], &[5,6,7,8]] );
+    let cached = Cached::new( &db, 1 << 10 );
+    let grids: Vec< ArrayGrid< 3, 3 > > = random_grids( &Goal::solved( ), 8, 60, 8 );
+
+    std::thread::scope( |s| {
+      for p in &grids {
+        let cached = &cached;
+        let db = &db;
+        s.spawn( move || assert_eq!( solve_ita( cached, *p ).1, solve_ita( db, *p ).1 ) );
+      }
+    } );
+    let (num_hits, num_misses) = cached.num_hits_misses( );
+    assert!( num_hits > 0 && num_misses > 0 );
+    assert!( grids.iter( ).all( |p| cached.compute( p ) == db.compute( p ) ) );
+  }
+
+  #[test]
+  #[should_panic( expected = "the heuristics count the moves of the same tiles" )]
+  fn overlapping_sums_panic( ) {
+    // Both count every tile
+    AdditiveSum::new( ManhattanDistance::< 3, 3 >::new( ), ManhattanDistance::< 3, 3 >::new( ) );
+  }
+}
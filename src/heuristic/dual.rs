@@ -0,0 +1,89 @@
+// local imports
+use crate::grid::PuzzleGrid;
+use super::general::Heuristic;
+
+
+/// Additionally looks up the wrapped heuristic on the *dual* grid, which swaps
+/// the roles of tiles and cells, and takes the maximum. (See
+/// [`PuzzleGrid::inverse()`], and *Dual lookups in pattern databases* (2005),
+/// by Ariel Felner, Uzi Zahavi, Jonathan Schaeffer and Robert C. Holte)
+///
+/// The moves which solve a grid, in reverse, solve its dual. But only when the
+/// gap is in its goal cell (i.e., the top-left cell) do they move the dual's
+/// gap. So only there is the dual equally far from the solved grid. Elsewhere,
+/// the gap first moves to the top-left cell, along its row and then up its
+/// column. The dual of that grid is looked up, less those moves. (As the grid
+/// is at most that many moves closer to the solved grid)
+///
+/// The wrapped heuristic must give distances to the solved puzzle. (e.g.,
+/// [`crate::DefaultPatternDB`], whose value is already the maximum of the
+/// regular and mirrored DB) The maximum is *inconsistent*, as neighbouring
+/// grids may take their values from different lookups. (See
+/// [`Heuristic::is_consistent()`])
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DualLookup< H >( pub H );
+
+impl< H, const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for DualLookup< H >
+  where H: Heuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    let hx = self.0.compute( p );
+
+    // Move the gap to the top-left cell
+    let mut rows = p.to_rows( );
+    let cells = rows.as_flattened_mut( );
+    let mut gap_idx = u8::from( p.find_gap( ) ) as usize;
+    let (x, y) = (gap_idx % WIDTH, gap_idx / WIDTH);
+    for _ in 0..x {
+      cells.swap( gap_idx, gap_idx - 1 );
+      gap_idx -= 1;
+    }
+    for _ in 0..y {
+      cells.swap( gap_idx, gap_idx - WIDTH );
+      gap_idx -= WIDTH;
+    }
+
+    let mut dual = [[0u8; WIDTH]; HEIGHT];
+    for (cell_idx, &tile) in cells.iter( ).enumerate( ) {
+      dual.as_flattened_mut( )[ tile as usize ] = cell_idx as u8;
+    }
+    hx.max( self.0.compute( &P::from( dual ) ).saturating_sub( ( x + y ) as u8 ) )
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    false
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{Board, Goal, PuzzleCell, PuzzleGrid};
+  use crate::heuristic::{Heuristic, PatternDB};
+  use crate::testing::bfs_distances;
+  use crate::verify::{VerifyConfig, verify_heuristic};
+  use super::DualLookup;
+
+  #[test]
+  fn dual_lookups_are_admissible( ) {
+    let distances = bfs_distances( &Goal::< 3, 3 >::solved( ), &Board::open( ) );
+    let db = PatternDB::< 3, 3 >::build( &[&[1,2,3,4], &[5,6,7,8]] );
+    let h = DualLookup( &db );
+
+    // The number of grids, with the gap elsewhere, where the dual raises the
+    // estimate
+    let mut num_raised = 0;
+    for (p, &distance) in &distances {
+      let hx = h.compute( p );
+      assert!( hx as u32 <= distance, "{:?}", p );
+      if hx > db.compute( p ) && p.find_gap( ) != PuzzleCell::from( 0 ) {
+        num_raised += 1;
+      }
+    }
+    assert!( num_raised > 0 );
+
+    let report = verify_heuristic( &h, &VerifyConfig { max_bfs_distance: 16, ..VerifyConfig::default( ) } );
+    assert!( report.is_ok( ), "{:?}", report.counterexamples );
+  }
+}
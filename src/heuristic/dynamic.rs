@@ -0,0 +1,111 @@
+// local imports
+use crate::grid::{ArrayGrid, PuzzleCell, PuzzleGrid, PuzzleTile};
+use super::general::Heuristic;
+
+
+/// An object-safe companion of [`Heuristic`], whose methods take the concrete
+/// [`ArrayGrid`] instead of any grid. So heuristics chosen at runtime (e.g.,
+/// from configuration) are stored as `Box< dyn DynHeuristic >`, which is a
+/// [`Heuristic`] itself, and is passed to the search unchanged.
+///
+/// Every [`Heuristic`] implements this. (Don't implement it directly) The
+/// boxed heuristic copies every grid into an [`ArrayGrid`], and makes a dynamic
+/// call, so the generic heuristics remain faster.
+///
+/// For instance:
+/// ```ignore
+/// let h: Box< dyn DynHeuristic + Sync > =
+///   match db {
+///     Some( db ) => Box::new( db ),
+///     None => Box::new( ManhattanDistance::new( ) ),
+///   };
+/// solve_ita( &h, grid );
+/// ```
+pub trait DynHeuristic< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// See [`Heuristic::compute()`]
+  fn dyn_compute( &self, v: &ArrayGrid< WIDTH, HEIGHT > ) -> u8;
+
+  /// See [`Heuristic::compute_moved()`]
+  fn dyn_compute_moved( &self, v: &ArrayGrid< WIDTH, HEIGHT >, hx: u8, tile: PuzzleTile< WIDTH, HEIGHT >, from_cell: PuzzleCell< WIDTH, HEIGHT >, to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u8;
+
+  /// See [`Heuristic::is_consistent()`]
+  fn dyn_is_consistent( &self ) -> bool;
+}
+
+impl< H: Heuristic< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > DynHeuristic< WIDTH, HEIGHT > for H {
+  #[inline]
+  fn dyn_compute( &self, v: &ArrayGrid< WIDTH, HEIGHT > ) -> u8 {
+    self.compute( v )
+  }
+
+  #[inline]
+  fn dyn_compute_moved( &self, v: &ArrayGrid< WIDTH, HEIGHT >, hx: u8, tile: PuzzleTile< WIDTH, HEIGHT >, from_cell: PuzzleCell< WIDTH, HEIGHT >, to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u8 {
+    self.compute_moved( v, hx, tile, from_cell, to_cell )
+  }
+
+  #[inline]
+  fn dyn_is_consistent( &self ) -> bool {
+    Heuristic::is_consistent( self )
+  }
+}
+
+impl< D, const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for Box< D >
+  where D: DynHeuristic< WIDTH, HEIGHT > + ?Sized
+{
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    ( **self ).dyn_compute( &ArrayGrid::from_grid( p ) )
+  }
+
+  #[inline]
+  fn compute_moved< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P, hx: u8, tile: PuzzleTile< WIDTH, HEIGHT >, from_cell: PuzzleCell< WIDTH, HEIGHT >, to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u8 {
+    ( **self ).dyn_compute_moved( &ArrayGrid::from_grid( p ), hx, tile, from_cell, to_cell )
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    ( **self ).dyn_is_consistent( )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{ArrayGrid, Dir, PackedGrid, PuzzleGrid};
+  use crate::heuristic::{DualLookup, Heuristic, ManhattanDistance, PatternDB};
+  use crate::search::{Puzzle, solve_ita};
+  use crate::testing::all_grids;
+  use super::DynHeuristic;
+
+  /// Checks the boxed heuristic against the static one, on both grid
+  /// representations, and after every move.
+  fn check_boxed< H: Heuristic< 3, 3 > >( h: &H, boxed: Box< dyn DynHeuristic< 3, 3 > + '_ > ) {
+    assert_eq!( boxed.is_consistent( ), h.is_consistent( ) );
+
+    for p in all_grids::< 3, 3 >( ).into_iter( ).step_by( 37 ) {
+      let hx = h.compute( &p );
+      assert_eq!( boxed.compute( &p ), hx, "{:?}", p );
+      assert_eq!( boxed.compute( &PackedGrid::< 3, 3 >::from( p.to_rows( ) ) ), hx, "{:?}", p );
+
+      let gap_cell = p.find_gap( );
+      for dir in Dir::ALL {
+        if let Some( moved ) = Puzzle::new( p ).step_inv( dir ) {
+          let tile_cell = gap_cell.step( dir ).unwrap( );
+          let tile = p.get_tile( tile_cell );
+          assert_eq!( boxed.compute_moved( moved.grid( ), hx, tile, tile_cell, gap_cell ), h.compute_moved( moved.grid( ), hx, tile, tile_cell, gap_cell ) );
+        }
+      }
+    }
+
+    let p = ArrayGrid::< 3, 3 >::from( [[8, 6, 7], [2, 5, 4], [3, 0, 1]] );
+    assert_eq!( solve_ita( &boxed, p ), solve_ita( h, p ) );
+  }
+
+  #[test]
+  fn boxed_heuristics_match_their_static_ones( ) {
+    let manhattan = ManhattanDistance::< 3, 3 >::new( );
+    let db = PatternDB::< 3, 3 >::build( &[&[1,2,3,4], &[5,6,7,8]] );
+    check_boxed( &manhattan, Box::new( manhattan ) );
+    check_boxed( &db, Box::new( &db ) );
+    check_boxed( &DualLookup( &db ), Box::new( DualLookup( &db ) ) );
+  }
+}
@@ -1,8 +1,10 @@
 // local imports
-use crate::grid::Puzzle24Grid;
+use crate::grid::{PuzzleCell, PuzzleGrid, PuzzleTile};
 
 
-pub trait Heuristic {
+/// A heuristic for puzzles on a `WIDTH`x`HEIGHT` grid. (By default, the
+/// 24-puzzle)
+pub trait Heuristic< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
   /// An admissible heuristic. It *underapproximates* the distance (in the search
   /// space) to the final puzzle.
   /// 
@@ -11,5 +13,115 @@ pub trait Heuristic {
   /// 
   /// No 24-puzzle needs more than 255 moves to the final solution.
   /// (TODO: source?)
-  fn compute< P: Puzzle24Grid >( &self, v: &P ) -> u8;
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P ) -> u8;
+
+  /// Like [`Heuristic::compute()`], but for the grid after `tile` moved from
+  /// `from_cell` into `to_cell`, where the heuristic was `hx` before. The
+  /// search calls this for every move, so heuristics which update
+  /// incrementally override this. (By default, it recomputes)
+  #[inline]
+  fn compute_moved< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P, _hx: u8, _tile: PuzzleTile< WIDTH, HEIGHT >, _from_cell: PuzzleCell< WIDTH, HEIGHT >, _to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u8 {
+    self.compute( v )
+  }
+
+  /// Whether the heuristic is *consistent*. That is, a move never decreases it
+  /// by more than 1. Heuristics which may be inconsistent (e.g., those which
+  /// look up different tables in different states) return `false`. Then,
+  /// [`crate::solve_ita_bpmx`] propagates their bounds between parents and
+  /// children. (i.e., bidirectional pathmax) The other searches ignore this.
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    true
+  }
+}
+
+/// A reference to a heuristic is one too. (e.g., to combine a heuristic with
+/// another one that borrows it, as in `Max( &db, DualLookup( &db ) )`)
+impl< H: Heuristic< WIDTH, HEIGHT > + ?Sized, const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for &H {
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P ) -> u8 {
+    ( **self ).compute( v )
+  }
+
+  #[inline]
+  fn compute_moved< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P, hx: u8, tile: PuzzleTile< WIDTH, HEIGHT >, from_cell: PuzzleCell< WIDTH, HEIGHT >, to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u8 {
+    ( **self ).compute_moved( v, hx, tile, from_cell, to_cell )
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    ( **self ).is_consistent( )
+  }
+}
+
+/// A [`Heuristic`] which only counts the moves of some tiles. That is, its
+/// value only depends on the cells of those tiles, and it underapproximates the
+/// number of *their* moves to the final puzzle. (e.g., a [`crate::PatternDB`],
+/// which counts the moves of its pattern tiles)
+///
+/// Heuristics which count disjoint tiles never count the same move, so their
+/// sum remains admissible. (See [`crate::AdditiveSum`])
+pub trait DisjointHeuristic< const WIDTH: usize = 5, const HEIGHT: usize = 5 >: Heuristic< WIDTH, HEIGHT > {
+  /// Whether the heuristic counts the moves of the (non-gap) tile.
+  fn counts_tile( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool;
+}
+
+impl< H: DisjointHeuristic< WIDTH, HEIGHT > + ?Sized, const WIDTH: usize, const HEIGHT: usize > DisjointHeuristic< WIDTH, HEIGHT > for &H {
+  #[inline]
+  fn counts_tile( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    ( **self ).counts_tile( tile )
+  }
+}
+
+/// A heuristic for puzzles where tiles have move costs. (See
+/// [`crate::TileCosts`])
+/// 
+/// Every tile costs at least 1, so every (unit-cost) [`Heuristic`] is also an
+/// admissible `CostHeuristic`.
+pub trait CostHeuristic< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// An admissible heuristic. It *underapproximates* the total cost of the
+  /// moves to the final puzzle.
+  fn compute_cost< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P ) -> u32;
+
+  /// Like [`CostHeuristic::compute_cost()`], but for the grid after `tile`
+  /// moved from `from_cell` into `to_cell`. (See [`Heuristic::compute_moved()`])
+  #[inline]
+  fn compute_cost_moved< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P, _hx: u32, _tile: PuzzleTile< WIDTH, HEIGHT >, _from_cell: PuzzleCell< WIDTH, HEIGHT >, _to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u32 {
+    self.compute_cost( v )
+  }
+
+  /// Whether no move decreases the heuristic by more than its cost. (See
+  /// [`Heuristic::is_consistent()`])
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    true
+  }
+}
+
+impl< H: Heuristic< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > CostHeuristic< WIDTH, HEIGHT > for H {
+  #[inline]
+  fn compute_cost< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P ) -> u32 {
+    self.compute( v ) as u32
+  }
+
+  #[inline]
+  fn compute_cost_moved< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P, hx: u32, tile: PuzzleTile< WIDTH, HEIGHT >, from_cell: PuzzleCell< WIDTH, HEIGHT >, to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u32 {
+    self.compute_moved( v, hx as u8, tile, from_cell, to_cell ) as u32
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    Heuristic::is_consistent( self )
+  }
+}
+
+/// A heuristic for the multi-tile metric, where sliding several tiles in one
+/// row or column counts as a single move. (See [`crate::solve_ita_slides()`])
+/// 
+/// Admissible heuristics for single-tile moves are generally *not* admissible
+/// for this metric. (See [`crate::SlideBound`])
+pub trait SlideHeuristic< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// An admissible heuristic. It *underapproximates* the number of slides to
+  /// the final puzzle.
+  fn compute_slides< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, v: &P ) -> u8;
 }
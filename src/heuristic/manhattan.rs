@@ -0,0 +1,157 @@
+// local imports
+use crate::grid::{Goal, PuzzleCell, PuzzleGrid, PuzzleTile, TileCosts};
+use super::general::{CostHeuristic, DisjointHeuristic, Heuristic};
+
+
+/// The sum of the Manhattan distances of all tiles (except the gap) to their
+/// cells in the goal. Every move brings only one tile one cell closer, so this
+/// is admissible.
+/// 
+/// It is much weaker than [`crate::DefaultPatternDB`], but needs no
+/// precomputation, and works for grids of any size. (So it suffices for 8- and
+/// 15-puzzles)
+/// 
+/// Note that, on large grids, the sum saturates at 255. That remains
+/// admissible.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ManhattanDistance< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: Goal< WIDTH, HEIGHT >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > ManhattanDistance< WIDTH, HEIGHT > {
+  /// The distance to the solved puzzle. (See [`Goal::solved()`])
+  #[inline]
+  pub const fn new( ) -> Self {
+    ManhattanDistance { goal: Goal::solved( ) }
+  }
+
+  /// The distance to the given goal.
+  #[inline]
+  pub const fn for_goal( goal: Goal< WIDTH, HEIGHT > ) -> Self {
+    ManhattanDistance { goal }
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for ManhattanDistance< WIDTH, HEIGHT > {
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    let mut sum = 0u8;
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let cell = PuzzleCell::< WIDTH, HEIGHT >::from( cell_idx as u8 );
+      let tile = p.get_tile( cell );
+
+      if tile != PuzzleTile::GAP {
+        let goal_cell = self.goal.cell( tile );
+        let dist = cell.x( ).abs_diff( goal_cell.x( ) ) + cell.y( ).abs_diff( goal_cell.y( ) );
+        sum = sum.saturating_add( dist );
+      }
+    }
+    sum
+  }
+
+  /// Only the moved tile's distance changes. (Unless the sum saturated)
+  #[inline]
+  fn compute_moved< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P, hx: u8, tile: PuzzleTile< WIDTH, HEIGHT >, from_cell: PuzzleCell< WIDTH, HEIGHT >, to_cell: PuzzleCell< WIDTH, HEIGHT > ) -> u8 {
+    if hx == u8::MAX {
+      return self.compute( p );
+    }
+
+    let goal_cell = self.goal.cell( tile );
+    let from_dist = from_cell.x( ).abs_diff( goal_cell.x( ) ) + from_cell.y( ).abs_diff( goal_cell.y( ) );
+    let to_dist = to_cell.x( ).abs_diff( goal_cell.x( ) ) + to_cell.y( ).abs_diff( goal_cell.y( ) );
+    ( hx - from_dist ).saturating_add( to_dist )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > DisjointHeuristic< WIDTH, HEIGHT > for ManhattanDistance< WIDTH, HEIGHT > {
+  #[inline]
+  fn counts_tile( &self, _tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    true
+  }
+}
+
+/// Like [`ManhattanDistance`], but on a torus, where tiles may move around
+/// the edges. (See [`crate::grid::Topology::Torus`]) Every tile's distance is
+/// the shorter way around, in both dimensions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TorusManhattanDistance< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: Goal< WIDTH, HEIGHT >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > TorusManhattanDistance< WIDTH, HEIGHT > {
+  /// The distance to the solved puzzle. (See [`Goal::solved()`])
+  #[inline]
+  pub const fn new( ) -> Self {
+    TorusManhattanDistance { goal: Goal::solved( ) }
+  }
+
+  /// The distance to the given goal.
+  #[inline]
+  pub const fn for_goal( goal: Goal< WIDTH, HEIGHT > ) -> Self {
+    TorusManhattanDistance { goal }
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for TorusManhattanDistance< WIDTH, HEIGHT > {
+  #[inline]
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    let mut sum = 0u8;
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let cell = PuzzleCell::< WIDTH, HEIGHT >::from( cell_idx as u8 );
+      let tile = p.get_tile( cell );
+
+      if tile != PuzzleTile::GAP {
+        let goal_cell = self.goal.cell( tile );
+        let dx = cell.x( ).abs_diff( goal_cell.x( ) );
+        let dy = cell.y( ).abs_diff( goal_cell.y( ) );
+        let dist = dx.min( WIDTH as u8 - dx ) + dy.min( HEIGHT as u8 - dy );
+        sum = sum.saturating_add( dist );
+      }
+    }
+    sum
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > DisjointHeuristic< WIDTH, HEIGHT > for TorusManhattanDistance< WIDTH, HEIGHT > {
+  #[inline]
+  fn counts_tile( &self, _tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    true
+  }
+}
+
+/// Like [`ManhattanDistance`], but every tile's distance is multiplied by its
+/// cost. Every move brings only one tile one cell closer, at its cost, so this
+/// is admissible.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WeightedManhattanDistance< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: Goal< WIDTH, HEIGHT >,
+  costs: TileCosts< WIDTH, HEIGHT >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > WeightedManhattanDistance< WIDTH, HEIGHT > {
+  #[inline]
+  pub const fn new( goal: Goal< WIDTH, HEIGHT >, costs: TileCosts< WIDTH, HEIGHT > ) -> Self {
+    WeightedManhattanDistance { goal, costs }
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > CostHeuristic< WIDTH, HEIGHT > for WeightedManhattanDistance< WIDTH, HEIGHT > {
+  #[inline]
+  fn compute_cost< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u32 {
+    let mut sum = 0u32;
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let cell = PuzzleCell::< WIDTH, HEIGHT >::from( cell_idx as u8 );
+      let tile = p.get_tile( cell );
+
+      if tile != PuzzleTile::GAP {
+        let goal_cell = self.goal.cell( tile );
+        let dist = cell.x( ).abs_diff( goal_cell.x( ) ) + cell.y( ).abs_diff( goal_cell.y( ) );
+        sum += dist as u32 * self.costs.cost( tile ) as u32;
+      }
+    }
+    sum
+  }
+}
@@ -0,0 +1,205 @@
+// local imports
+use crate::grid::{LabelGoal, PuzzleCell, PuzzleGrid, PuzzleTile};
+use super::general::Heuristic;
+
+
+/// Like [`crate::ManhattanDistance`], but for tiles with labels. (See
+/// [`LabelGoal`]) Tiles with the same label may end up in any of their goal
+/// cells. So, for every label, we take the assignment of its tiles to its goal
+/// cells with the minimal sum of Manhattan distances. (i.e., we solve the
+/// assignment problem, with the Hungarian algorithm)
+/// 
+/// Every move brings only one tile one cell closer to any goal cell, so this is
+/// admissible. Like [`crate::ManhattanDistance`], the sum saturates at 255.
+#[derive(Clone, Debug)]
+pub struct MatchingDistance< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: LabelGoal< WIDTH, HEIGHT >,
+  /// The group of every tile, where groups contain the tiles with the same
+  /// label. (The gap is in no group)
+  tile_groups: Vec< usize >,
+  /// The goal cells of all groups, where group `i` has the cells in
+  /// `group_starts[ i ]..group_starts[ i + 1 ]`
+  goal_cells: Vec< u8 >,
+  group_starts: Vec< usize >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > MatchingDistance< WIDTH, HEIGHT > {
+  pub fn new( goal: LabelGoal< WIDTH, HEIGHT > ) -> Self {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    // The group of every label
+    let mut label_groups: [Option< usize >; 256] = [None; 256];
+    let mut tile_groups = vec![ 0; num_cells ];
+    let mut goal_cells: Vec< Vec< u8 > > = Vec::new( );
+
+    for tile in 1..num_cells {
+      let tile = PuzzleTile::from( tile as u8 );
+      let label = goal.label( tile ) as usize;
+      let group = *label_groups[ label ].get_or_insert_with( || {
+        goal_cells.push( Vec::new( ) );
+        goal_cells.len( ) - 1
+      } );
+
+      tile_groups[ u8::from( tile ) as usize ] = group;
+      goal_cells[ group ].push( u8::from( goal.goal( ).cell( tile ) ) );
+    }
+
+    let mut group_starts = vec![ 0 ];
+    for cells in &goal_cells {
+      group_starts.push( group_starts[ group_starts.len( ) - 1 ] + cells.len( ) );
+    }
+    let goal_cells = goal_cells.concat( );
+    MatchingDistance { goal, tile_groups, goal_cells, group_starts }
+  }
+
+  /// Returns the label goal.
+  #[inline]
+  pub fn goal( &self ) -> &LabelGoal< WIDTH, HEIGHT > {
+    &self.goal
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for MatchingDistance< WIDTH, HEIGHT > {
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    // The current cells of all groups. (Like `goal_cells`)
+    let mut cells = [0u8; 256];
+    // The number of cells of every group, so far
+    let mut num_group_cells = [0usize; 256];
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let tile = p.get_tile( PuzzleCell::from( cell_idx as u8 ) );
+
+      if tile != PuzzleTile::GAP {
+        let group = self.tile_groups[ u8::from( tile ) as usize ];
+        cells[ self.group_starts[ group ] + num_group_cells[ group ] ] = cell_idx as u8;
+        num_group_cells[ group ] += 1;
+      }
+    }
+
+    let mut sum = 0u32;
+    for range in self.group_starts.windows( 2 ) {
+      let (start, end) = ( range[ 0 ], range[ 1 ] );
+      sum += min_cost_assignment::< WIDTH >( &cells[ start..end ], &self.goal_cells[ start..end ] );
+    }
+    sum.min( 0xFF ) as u8
+  }
+}
+
+/// Returns the Manhattan distance between the cells.
+#[inline]
+fn distance< const WIDTH: usize >( a: u8, b: u8 ) -> u32 {
+  let (a, b) = ( a as usize, b as usize );
+  ( ( a % WIDTH ).abs_diff( b % WIDTH ) + ( a / WIDTH ).abs_diff( b / WIDTH ) ) as u32
+}
+
+/// Returns the minimal sum of distances over all assignments of the `from`
+/// cells to the `to` cells. (Which are equally many)
+/// 
+/// This is the Hungarian algorithm, which takes `O(n^3)` time. It adds rows one
+/// by one, and maintains potentials `u` (of rows) and `v` (of columns), such
+/// that the assigned entries are tight.
+fn min_cost_assignment< const WIDTH: usize >( from: &[u8], to: &[u8] ) -> u32 {
+  let n = from.len( );
+  match n {
+    0 => return 0,
+    1 => return distance::< WIDTH >( from[ 0 ], to[ 0 ] ),
+    _ => { },
+  }
+
+  // Index 0 is a dummy column, from which every row is added. (Groups have at
+  // most 255 tiles)
+  let mut u = [0i32; 257];
+  let mut v = [0i32; 257];
+  // The row assigned to every column
+  let mut col_rows = [0usize; 257];
+  // The previous column on the augmenting path
+  let mut way = [0usize; 257];
+  let mut min_v = [0i32; 257];
+  let mut is_used = [false; 257];
+
+  for row in 1..=n {
+    col_rows[ 0 ] = row;
+    let mut col0 = 0;
+    min_v[ ..=n ].fill( i32::MAX );
+    is_used[ ..=n ].fill( false );
+
+    loop {
+      is_used[ col0 ] = true;
+      let row0 = col_rows[ col0 ];
+      let mut delta = i32::MAX;
+      let mut col1 = 0;
+
+      for col in 1..=n {
+        if !is_used[ col ] {
+          let cur = distance::< WIDTH >( from[ row0 - 1 ], to[ col - 1 ] ) as i32 - u[ row0 ] - v[ col ];
+          if cur < min_v[ col ] {
+            min_v[ col ] = cur;
+            way[ col ] = col0;
+          }
+          if min_v[ col ] < delta {
+            delta = min_v[ col ];
+            col1 = col;
+          }
+        }
+      }
+
+      for col in 0..=n {
+        if is_used[ col ] {
+          u[ col_rows[ col ] ] += delta;
+          v[ col ] -= delta;
+        } else {
+          min_v[ col ] -= delta;
+        }
+      }
+      col0 = col1;
+
+      if col_rows[ col0 ] == 0 {
+        break;
+      }
+    }
+
+    // Augment along the path
+    while col0 != 0 {
+      let col1 = way[ col0 ];
+      col_rows[ col0 ] = col_rows[ col1 ];
+      col0 = col1;
+    }
+  }
+  ( -v[ 0 ] ) as u32
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::testing::permutations;
+  use crate::verify::SplitMix64;
+  use super::{distance, min_cost_assignment};
+
+  /// Tries every assignment.
+  fn brute_force_assignment< const WIDTH: usize >( from: &[u8], to: &[u8] ) -> u32 {
+    permutations( from.len( ) ).iter( ).map( |p| {
+      from.iter( ).zip( p ).map( |(&a, &i)| distance::< WIDTH >( a, to[ i as usize ] ) ).sum( )
+    } ).min( ).unwrap_or( 0 )
+  }
+
+  #[test]
+  fn hungarian_matches_brute_force( ) {
+    let mut rng = SplitMix64( 7 );
+
+    for n in 0..=7 {
+      for _ in 0..50 {
+        // Distinct cells, as tiles are in distinct cells (and so are goals)
+        let mut cells: Vec< u8 > = ( 0..25 ).collect( );
+        for i in ( 1..cells.len( ) ).rev( ) {
+          cells.swap( i, ( rng.next( ) % ( i as u64 + 1 ) ) as usize );
+        }
+        let from = &cells[ ..n ];
+        let mut to = cells[ 25 - n.. ].to_vec( );
+        // Goal cells may overlap current cells
+        if n > 1 && rng.next( ).is_multiple_of( 2 ) {
+          to[ 0 ] = from[ 1 ];
+        }
+
+        assert_eq!( min_cost_assignment::< 5 >( from, &to ), brute_force_assignment::< 5 >( from, &to ), "{:?} to {:?}", from, to );
+      }
+    }
+  }
+}
@@ -1,5 +1,19 @@
 mod general;
+mod board_distance;
+mod combinators;
+mod dual;
+mod dynamic;
+mod manhattan;
+mod matching;
 mod pattern_db;
+mod slides;
 
-pub use general::Heuristic;
-pub use pattern_db::{Pattern6Puzzle, DefaultPatternDB};
+pub use general::{CostHeuristic, DisjointHeuristic, Heuristic, SlideHeuristic};
+pub use board_distance::BoardDistance;
+pub use combinators::{AdditiveSum, Cached, Max};
+pub use dual::DualLookup;
+pub use dynamic::DynHeuristic;
+pub use manhattan::{ManhattanDistance, TorusManhattanDistance, WeightedManhattanDistance};
+pub use matching::MatchingDistance;
+pub use pattern_db::{Pattern6Puzzle, DefaultPatternDB, PatternDB, WeightedPatternDB, MAX_PATTERN_SIZE, MAX_NUM_PATTERNS};
+pub use slides::{MaxDisplacement, SlideBound};
@@ -1,6 +1,9 @@
+// stdlib imports
+use std::{fs, io};
+use std::path::Path;
 // local imports
-use crate::grid::{ArrayGrid24, Dir, Puzzle24Grid, PuzzleCell, PuzzleTile};
-use super::super::general::Heuristic;
+use crate::grid::{ArrayGrid24, Dir, PuzzleGrid, PuzzleCell, PuzzleTile};
+use super::super::general::{DisjointHeuristic, Heuristic};
 use super::patterns::*;
 use super::packings::*;
 use super::helpers::*;
@@ -45,7 +48,7 @@ impl Pattern6Puzzle {
       // note that, for the final puzzle, tiles are identical to cells
       let tile = cell_idx;
 
-      let (p_id, p_idx) = pattern[ tile as usize ];
+      let (p_id, p_idx) = pattern[ tile ];
 
       if pattern_id == p_id {
         // Doing `+ 1`, because the index in a pattern starts at 0. However,
@@ -77,7 +80,7 @@ impl Pattern6Puzzle {
 
     // The tile we move into the gap
     let moved_tile = self.grid.get_tile( adj_cell );
-    let mut new_grid = self.grid.clone( );
+    let mut new_grid = self.grid;
     new_grid.set_tile( self.gap_cell, moved_tile );
     new_grid.clear_cell( adj_cell );
 
@@ -94,7 +97,7 @@ impl Pattern6Puzzle {
   }
 
   #[inline]
-  fn to_u32( &self ) -> Pattern6Packing {
+  fn to_u32( self ) -> Pattern6Packing {
     let mut packing_u32 = Pattern6Packing::default( );
 
     for cell_idx in 0..25 {
@@ -102,7 +105,7 @@ impl Pattern6Puzzle {
       let tile = self.grid.get_tile( cell_idx );
       let tile_u8 = u8::from( tile );
 
-      if tile_u8 >= 1 && tile_u8 <= 6 { // we do the gap separately
+      if ( 1..=6 ).contains( &tile_u8 ) { // we do the gap separately
         packing_u32.set_tile_unchecked( tile_u8 - 1, cell_idx );
       }
     }
@@ -158,7 +161,7 @@ impl Pattern6Puzzle {
   }
 
   #[inline]
-  pub fn extract_u32s< P: Puzzle24Grid >( p: &P ) -> ([u32; 4], [u32; 4]) {
+  pub fn extract_u32s< P: PuzzleGrid >( p: &P ) -> ([u32; 4], [u32; 4]) {
     // Matches pattern tiles to their cell in the puzzle
     let mut tile_to_cell = [Pattern6Packing::default( ); 4];
     let mut mirror_tile_to_cell = [Pattern6Packing::default( ); 4];
@@ -212,6 +215,9 @@ impl Pattern6Puzzle {
 /// We obtain pattern 2 and 3 by rotating pattern 1. We additionally compute the
 /// value of the mirrored DB, with which we compute the maximum. (See
 /// [`DefaultPatternDB::compute()`])
+/// 
+/// Note that it only gives distances to the solved puzzle. (For other goals,
+/// see [`crate::PatternDB::build_for_goal()`])
 pub struct DefaultPatternDB {
   default_pattern0: Vec< u8 >,
   default_pattern1: Vec< u8 >
@@ -239,10 +245,28 @@ impl DefaultPatternDB {
   pub fn patterns( &self ) -> (&[u8], &[u8]) {
     (&self.default_pattern0, &self.default_pattern1)
   }
+
+  /// Reads the patterns from two files, as written by [`DefaultPatternDB::save()`].
+  pub fn load< P: AsRef< Path >, Q: AsRef< Path > >( pattern0_path: P, pattern1_path: Q ) -> io::Result< Self > {
+    let default_pattern0 = fs::read( pattern0_path )?;
+    let default_pattern1 = fs::read( pattern1_path )?;
+
+    if default_pattern0.len( ) != 244_140_625 || default_pattern1.len( ) != 244_140_625 {
+      return Err( io::Error::new( io::ErrorKind::InvalidData, "pattern file has the wrong size" ) );
+    }
+
+    Ok( DefaultPatternDB { default_pattern0, default_pattern1 } )
+  }
+
+  /// Writes the (raw) patterns to two files. Each file is 244MB.
+  pub fn save< P: AsRef< Path >, Q: AsRef< Path > >( &self, pattern0_path: P, pattern1_path: Q ) -> io::Result< () > {
+    fs::write( pattern0_path, &self.default_pattern0 )?;
+    fs::write( pattern1_path, &self.default_pattern1 )
+  }
 }
 
 impl Heuristic for DefaultPatternDB {
-  fn compute< P: Puzzle24Grid >( &self, p: &P ) -> u8 {
+  fn compute< P: PuzzleGrid >( &self, p: &P ) -> u8 {
     let (p0, p1) = Pattern6Puzzle::extract_u32s( p );
 
     let dis_a0 = self.default_pattern0[ p0[ 0 ] as usize ];
@@ -262,6 +286,20 @@ impl Heuristic for DefaultPatternDB {
     // so take their maximum
     dis_a.max( dis_b )
   }
+
+  /// The tables keep the smallest distance over all cells of the gap. (See
+  /// [`crate::PatternDB::is_consistent()`])
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    false
+  }
+}
+
+impl DisjointHeuristic for DefaultPatternDB {
+  #[inline]
+  fn counts_tile( &self, _tile: PuzzleTile ) -> bool {
+    true
+  }
 }
 
 impl From< DefaultPatternDB > for (Vec< u8 >, Vec< u8 >) {
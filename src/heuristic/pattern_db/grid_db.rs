@@ -0,0 +1,388 @@
+// local imports
+use crate::grid::{Board, Dir, Goal, PartialGoal, PuzzleCell, PuzzleGrid, PuzzleTile, TileCosts};
+use super::super::general::{CostHeuristic, DisjointHeuristic, Heuristic};
+use super::helpers::BitSet;
+
+
+/// The maximum number of tiles in a pattern of a [`PatternDB`].
+pub const MAX_PATTERN_SIZE: usize = 8;
+
+/// The maximum number of patterns in a [`PatternDB`].
+pub const MAX_NUM_PATTERNS: usize = 16;
+
+/// A disjoint pattern database for a grid of any size (including non-square
+/// ones), with patterns given at runtime. Unlike [`super::DefaultPatternDB`],
+/// this is not tuned for the 24-puzzle. It is intended for small boards (e.g.,
+/// the 8- and 15-puzzles), where the tables are small.
+///
+/// The table of a pattern with `k` tiles has `(WIDTH*HEIGHT)^k` entries.
+/// Building it visits up to `(WIDTH*HEIGHT)^(k+1)` states. (e.g., a 5-tile
+/// pattern of the 15-puzzle has a table of 1MB)
+///
+/// On square grids, we additionally look up the puzzle mirrored along the
+/// diagonal, and take the maximum. (Like [`super::DefaultPatternDB`]) That
+/// symmetry does not exist for non-square grids, or for goals with their gap
+/// off the diagonal, where we skip it.
+pub struct PatternDB< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// For every tile, the pattern containing it, with the factor of its cell
+  /// in the pattern's table index. (The gap is in no pattern)
+  tile_patterns: Vec< Option< (u8, usize) > >,
+  /// The distances for every pattern, indexed by the cells of its tiles. (See
+  /// [`PatternDB::pattern_idx()`])
+  tables: Vec< Vec< u8 > >,
+  /// Maps every cell to the one mirrored along the diagonal, and every tile to
+  /// the one with the mirrored goal cell. (See [`PatternDB::is_mirrored()`])
+  mirror: Option< (Vec< u8 >, Vec< u8 >) >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > PatternDB< WIDTH, HEIGHT > {
+  /// Builds the tables for the given patterns, which must partition all
+  /// non-gap tiles. There are at most [`MAX_NUM_PATTERNS`] patterns, which each
+  /// contain at most [`MAX_PATTERN_SIZE`] tiles.
+  ///
+  /// For instance, `&[&[1,2,3,4,5,6,7,8]]` is a (single-pattern) DB for the
+  /// 8-puzzle, which gives exact distances.
+  pub fn build( patterns: &[&[u8]] ) -> Self {
+    PatternDB::build_for_goal( &Goal::solved( ), patterns )
+  }
+
+  /// Like [`PatternDB::build()`], but for the distances to the given goal.
+  pub fn build_for_goal( goal: &Goal< WIDTH, HEIGHT >, patterns: &[&[u8]] ) -> Self {
+    PatternDB::build_on_board( goal, &Board::open( ), patterns )
+  }
+
+  /// Like [`PatternDB::build_for_goal()`], but on a board with blocked cells.
+  /// The tiles in blocked cells never move, so the patterns must partition
+  /// all other non-gap tiles. (On a torus, the tables use its wrap-around
+  /// moves; see [`Board::torus()`])
+  pub fn build_on_board( goal: &Goal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT >, patterns: &[&[u8]] ) -> Self {
+    PatternDB::build_with( &PartialGoal::from( *goal ), board, &TileCosts::unit( ), patterns )
+  }
+
+  /// Like [`PatternDB::build_for_goal()`], but for the distances to the
+  /// partial goal. (See [`crate::solve_ita_partial()`]) The patterns must
+  /// partition the cared-about tiles (without the gap). Don't-care tiles are in
+  /// no pattern, so their moves are free.
+  /// 
+  /// Unless the gap is cared about, the tables contain the distances to the
+  /// nearest goal with the gap in any cell.
+  pub fn build_partial( goal: &PartialGoal< WIDTH, HEIGHT >, patterns: &[&[u8]] ) -> Self {
+    PatternDB::build_with( goal, &Board::open( ), &TileCosts::unit( ), patterns )
+  }
+
+  /// Like [`PatternDB::build_partial()`], but on a board with blocked cells,
+  /// where the tables contain the total cost of the pattern tiles' moves.
+  /// (Capped at 255, which is admissible)
+  fn build_with( partial_goal: &PartialGoal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT >, patterns: &[&[u8]] ) -> Self {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let goal = partial_goal.goal( );
+    let mut tile_patterns = vec![ None; num_cells ];
+    // Tiles in blocked cells are in no pattern, and neither are don't-care tiles
+    let is_fixed = |tile: usize| board.is_blocked( goal.cell( PuzzleTile::from( tile as u8 ) ) );
+    let is_cared = |tile: usize| partial_goal.is_cared( PuzzleTile::from( tile as u8 ) );
+
+    if is_cared( 0 ) {
+      assert!( !board.is_blocked( goal.gap_cell( ) ), "the goal's gap is in a blocked cell" );
+    }
+
+    assert!( patterns.len( ) <= MAX_NUM_PATTERNS, "too many patterns" );
+    for (pattern_id, pattern) in patterns.iter( ).enumerate( ) {
+      assert!( !pattern.is_empty( ) && pattern.len( ) <= MAX_PATTERN_SIZE, "pattern has an unsupported size" );
+
+      for (pattern_idx, &tile) in pattern.iter( ).enumerate( ) {
+        assert!( tile != 0 && ( tile as usize ) < num_cells, "pattern contains an invalid tile" );
+        assert!( !is_fixed( tile as usize ), "pattern contains a tile in a blocked cell" );
+        assert!( is_cared( tile as usize ), "pattern contains a don't-care tile" );
+        assert!( tile_patterns[ tile as usize ].is_none( ), "patterns overlap" );
+        tile_patterns[ tile as usize ] = Some( (pattern_id as u8, num_cells.pow( pattern_idx as u32 )) );
+      }
+    }
+    assert!( ( 1..num_cells ).all( |t| tile_patterns[ t ].is_some( ) || is_fixed( t ) || !is_cared( t ) ), "patterns do not cover all tiles" );
+
+    let tables = patterns.iter( ).map( |pattern| Self::build_table( partial_goal, board, costs, pattern ) ).collect( );
+
+    // Mirroring maps the goal onto itself, and the gap onto the gap, iff the
+    // goal's gap is on the diagonal. Mirrored tiles must cost the same, and
+    // must both be cared about or not. Mirrored cells must both be blocked or
+    // open.
+    let gap_cell = goal.gap_cell( );
+    let mirror =
+      if WIDTH == HEIGHT && gap_cell.x( ) == gap_cell.y( ) {
+        let mirror_cells: Vec< u8 > =
+          ( 0..num_cells ).map( |c| ( ( c % WIDTH ) * WIDTH + c / WIDTH ) as u8 ).collect( );
+        let mirror_tiles: Vec< u8 > =
+          ( 0..num_cells ).map( |t| {
+            let goal_cell = goal.cell( PuzzleTile::from( t as u8 ) );
+            u8::from( goal.tile( PuzzleCell::from( mirror_cells[ u8::from( goal_cell ) as usize ] ) ) )
+          } ).collect( );
+        let is_symmetric =
+          ( 0..num_cells ).all( |t| {
+            costs.cost( PuzzleTile::from( t as u8 ) ) == costs.cost( PuzzleTile::from( mirror_tiles[ t ] ) ) &&
+              is_cared( t ) == is_cared( mirror_tiles[ t ] as usize )
+          } ) &&
+          ( 0..num_cells ).all( |c| {
+            board.is_blocked( PuzzleCell::from( c as u8 ) ) == board.is_blocked( PuzzleCell::from( mirror_cells[ c ] ) )
+          } );
+
+        if is_symmetric { Some( (mirror_cells, mirror_tiles) ) } else { None }
+      } else {
+        None
+      };
+
+    PatternDB { tile_patterns, tables, mirror }
+  }
+
+  /// Returns whether the mirrored puzzle is looked up too. (i.e., whether the
+  /// grid is square, the goal's gap is on its diagonal, and the board is
+  /// symmetric; and, for [`WeightedPatternDB`], mirrored tiles cost the same)
+  #[inline]
+  pub fn is_mirrored( &self ) -> bool {
+    self.mirror.is_some( )
+  }
+
+  /// The index into the table of a pattern, where `cells` contains the cell
+  /// of every pattern tile.
+  #[inline]
+  fn pattern_idx( cells: &[u8] ) -> usize {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    cells.iter( ).rev( ).fold( 0, |acc, &cell| acc * num_cells + cell as usize )
+  }
+
+  /// Computes the distances for a single pattern, with a breadth-first search
+  /// (from the goal). Only moves of pattern tiles count, at their cost; other
+  /// moves are free. (Like [`super::Pattern6Puzzle::build_pattern_db()`]) The
+  /// gap never moves into blocked cells. Unless the gap is cared about, the
+  /// search starts from the goal with the gap in every (other) open cell.
+  /// 
+  /// With unit costs, this is a 0-1 breadth-first search. Otherwise, we keep a
+  /// queue for every distance modulo the maximum cost (i.e., Dial's algorithm).
+  fn build_table( partial_goal: &PartialGoal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT >, pattern: &[u8] ) -> Vec< u8 > {
+    let num_cells = PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS;
+    let num_entries = num_cells.pow( pattern.len( ) as u32 );
+    let mut distances = vec![ 0xFF; num_entries ];
+    // States include the gap
+    let mut visited = BitSet::new( num_entries * num_cells );
+
+    let goal = partial_goal.goal( );
+    let mut start = PatternState { cells: [0; MAX_PATTERN_SIZE], gap_cell: goal.gap_cell( ) };
+    for (cell, &tile) in start.cells.iter_mut( ).zip( pattern ) {
+      *cell = u8::from( goal.cell( PuzzleTile::from( tile ) ) );
+    }
+    let starts: Vec< PatternState< WIDTH, HEIGHT > > =
+      if partial_goal.is_cared( PuzzleTile::GAP ) {
+        vec![ start ]
+      } else {
+        ( 0..num_cells )
+          .map( |c| PuzzleCell::from( c as u8 ) )
+          .filter( |&c| !board.is_blocked( c ) && !start.cells[ ..pattern.len( ) ].contains( &u8::from( c ) ) )
+          .map( |gap_cell| {
+            let mut p = start;
+            p.gap_cell = gap_cell;
+            p
+          } )
+          .collect( )
+      };
+
+    let pattern_costs: Vec< u8 > = pattern.iter( ).map( |&tile| costs.cost( PuzzleTile::from( tile ) ) ).collect( );
+    let max_cost = pattern_costs.iter( ).copied( ).max( ).unwrap_or( 1 ) as usize;
+
+    let mut depth = 0;
+    // the queue at depth `depth + i` is `queues[ ( depth + i ) % queues.len( ) ]`
+    let mut queues = vec![ Vec::new( ); max_cost + 1 ];
+    let mut num_queued = starts.len( );
+    queues[ 0 ] = starts;
+
+    while num_queued > 0 {
+      let curr_idx = depth % queues.len( );
+      let mut curr_queue = std::mem::take( &mut queues[ curr_idx ] );
+
+      while let Some( p ) = curr_queue.pop( ) {
+        num_queued -= 1;
+        let cells = &p.cells[ ..pattern.len( ) ];
+        let idx = Self::pattern_idx( cells );
+        let state_idx = idx * num_cells + u8::from( p.gap_cell ) as usize;
+
+        if unsafe { visited.insert_unchecked( state_idx ) } {
+          if distances[ idx ] == 0xFF {
+            distances[ idx ] = depth.min( 0xFF ) as u8;
+          }
+
+          for dir in Dir::ALL {
+            if let Some( adj_cell ) = board.step( p.gap_cell, dir ) {
+              let mut next_p = p;
+              next_p.gap_cell = adj_cell;
+              num_queued += 1;
+
+              match cells.iter( ).position( |&c| c == u8::from( adj_cell ) ) {
+                Some( i ) => { // a pattern tile moves into the gap
+                  next_p.cells[ i ] = u8::from( p.gap_cell );
+                  let next_idx = ( depth + pattern_costs[ i ] as usize ) % queues.len( );
+                  queues[ next_idx ].push( next_p );
+                },
+                None => curr_queue.push( next_p ),
+              }
+            }
+          }
+        }
+      }
+      // assert: curr_queue.is_empty( )
+      queues[ curr_idx ] = curr_queue; // reuses its allocation
+      depth += 1;
+    }
+    distances
+  }
+
+  /// Sums the distances of all patterns. If `mirror` is given, this looks up
+  /// the puzzle mirrored with it. (i.e., the cells and tiles are mapped)
+  #[inline]
+  fn lookup< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P, mirror: Option< (&[u8], &[u8]) > ) -> u32 {
+    // The index into the table of every pattern. (See `PatternDB::pattern_idx`)
+    let mut idxs = [0usize; MAX_NUM_PATTERNS];
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let tile = p.get_tile( PuzzleCell::from( cell_idx as u8 ) );
+
+      if tile != PuzzleTile::GAP {
+        let (tile, cell) =
+          match mirror {
+            Some( (mirror_cells, mirror_tiles) ) =>
+              ( mirror_tiles[ u8::from( tile ) as usize ], mirror_cells[ cell_idx ] ),
+            None => ( u8::from( tile ), cell_idx as u8 ),
+          };
+        if let Some( (pattern_id, factor) ) = self.tile_patterns[ tile as usize ] {
+          idxs[ pattern_id as usize ] += factor * cell as usize;
+        }
+      }
+    }
+
+    let mut sum = 0u32;
+    for (table, &idx) in self.tables.iter( ).zip( idxs.iter( ) ) {
+      sum += table[ idx ] as u32;
+    }
+    sum
+  }
+
+  /// The maximum of the regular and mirrored lookup.
+  #[inline]
+  fn lookup_max< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u32 {
+    let dis = self.lookup( p, None );
+
+    match &self.mirror {
+      // both are (independently) admissible heuristic values, so take their
+      // maximum
+      Some( (mirror_cells, mirror_tiles) ) => dis.max( self.lookup( p, Some( (mirror_cells, mirror_tiles) ) ) ),
+      None => dis,
+    }
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > Heuristic< WIDTH, HEIGHT > for PatternDB< WIDTH, HEIGHT > {
+  fn compute< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    self.lookup_max( p ).min( 0xFF ) as u8
+  }
+
+  /// The tables keep the smallest distance over all cells of the gap. So, a
+  /// move may decrease a table's value by more than 1. (e.g., when the gap's
+  /// way back around the moved tile is long)
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    false
+  }
+}
+
+/// Counts the moves of the pattern tiles. (So, with [`PatternDB::build_partial()`],
+/// only those of cared-about tiles)
+impl< const WIDTH: usize, const HEIGHT: usize > DisjointHeuristic< WIDTH, HEIGHT > for PatternDB< WIDTH, HEIGHT > {
+  #[inline]
+  fn counts_tile( &self, tile: PuzzleTile< WIDTH, HEIGHT > ) -> bool {
+    self.tile_patterns[ u8::from( tile ) as usize ].is_some( )
+  }
+}
+
+/// A [`PatternDB`] for tiles with move costs. Its tables contain the minimal
+/// total cost of the pattern tiles' moves. As every move is only charged to the
+/// pattern containing its tile, the sum over the patterns remains admissible.
+/// 
+/// Note that table entries are capped at 255.
+pub struct WeightedPatternDB< const WIDTH: usize = 5, const HEIGHT: usize = 5 >( PatternDB< WIDTH, HEIGHT > );
+
+impl< const WIDTH: usize, const HEIGHT: usize > WeightedPatternDB< WIDTH, HEIGHT > {
+  /// Builds the tables for the given patterns. (See [`PatternDB::build()`])
+  pub fn build( goal: &Goal< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT >, patterns: &[&[u8]] ) -> Self {
+    WeightedPatternDB::build_on_board( goal, &Board::open( ), costs, patterns )
+  }
+
+  /// Like [`WeightedPatternDB::build()`], but on a board with blocked cells.
+  /// (See [`PatternDB::build_on_board()`])
+  pub fn build_on_board( goal: &Goal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT >, patterns: &[&[u8]] ) -> Self {
+    WeightedPatternDB( PatternDB::build_with( &PartialGoal::from( *goal ), board, costs, patterns ) )
+  }
+
+  /// See [`PatternDB::is_mirrored()`]
+  #[inline]
+  pub fn is_mirrored( &self ) -> bool {
+    self.0.is_mirrored( )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > CostHeuristic< WIDTH, HEIGHT > for WeightedPatternDB< WIDTH, HEIGHT > {
+  fn compute_cost< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u32 {
+    self.0.lookup_max( p )
+  }
+
+  /// See [`PatternDB::is_consistent()`]
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    false
+  }
+}
+
+/// A state while building a pattern table.
+#[derive(Clone, Copy)]
+struct PatternState< const WIDTH: usize, const HEIGHT: usize > {
+  /// The cell of every pattern tile
+  cells: [u8; MAX_PATTERN_SIZE],
+  gap_cell: PuzzleCell< WIDTH, HEIGHT >,
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{Board, Goal, TileCosts};
+  use crate::heuristic::{CostHeuristic, WeightedManhattanDistance};
+  use crate::search::{Budget, SearchOutcome, solve_ita_board};
+  use crate::testing::dijkstra_costs;
+  use super::WeightedPatternDB;
+
+  /// Checks the tables and the search against the exact costs of every grid.
+  /// A single pattern with all (open) tiles gives exact costs.
+  fn check_costs< const WIDTH: usize, const HEIGHT: usize >( goal: &Goal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT >, all_tiles: &[u8], patterns: &[&[u8]] ) {
+    let exact = WeightedPatternDB::build_on_board( goal, board, costs, &[ all_tiles ] );
+    let db = WeightedPatternDB::build_on_board( goal, board, costs, patterns );
+    let manhattan = WeightedManhattanDistance::new( *goal, *costs );
+
+    for (p, cost) in dijkstra_costs( goal, board, costs ) {
+      assert_eq!( exact.compute_cost( &p ), cost, "{:?}", p );
+      assert!( db.compute_cost( &p ) <= cost, "{:?}", p );
+      if board.is_open( ) {
+        assert!( manhattan.compute_cost( &p ) <= cost, "{:?}", p );
+      }
+
+      let SearchOutcome::Solved( path ) = solve_ita_board( &db, p, goal, board, costs, Budget::default( ) ).0 else {
+        panic!( "{:?} is unsolved", p );
+      };
+      assert_eq!( costs.path_cost( &p, &path ), Some( cost ), "{:?}", p );
+    }
+  }
+
+  #[test]
+  fn weighted_tables_match_dijkstra( ) {
+    let costs = TileCosts::< 2, 3 >::new( &[0, 1, 3, 2, 1, 4] );
+    check_costs( &Goal::solved( ), &Board::open( ), &costs, &[1,2,3,4,5], &[ &[1,2], &[3,4,5] ] );
+    check_costs( &Goal::snake( ), &Board::open( ), &costs, &[1,2,3,4,5], &[ &[1,5], &[2,3,4] ] );
+
+    // The open cells form a cycle around the blocked centre
+    let costs = TileCosts::< 3, 3 >::new( &[0, 2, 1, 3, 9, 1, 2, 5, 1] );
+    let board = Board::from_rows( &[[false, false, false], [false, true, false], [false, false, false]] );
+    check_costs( &Goal::solved( ), &board, &costs, &[1,2,3,5,6,7,8], &[ &[1,2,3], &[5,6,7,8] ] );
+  }
+}
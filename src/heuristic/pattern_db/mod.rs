@@ -1,12 +1,16 @@
-///! This implements disjoint pattern databases as taken from:
-/// "Disjoint pattern database heuristics" by Richard E. Korf, and Ariel Felner
-/// 
-/// We mostly hard-code the "default" shaped patterns ([`PATTERN_IDX`]) for
-/// performance reasons.
+//! This implements disjoint pattern databases as taken from:
+//! "Disjoint pattern database heuristics" by Richard E. Korf, and Ariel Felner
+//! 
+//! We mostly hard-code the "default" shaped patterns ([`PATTERN_IDX`]) for
+//! performance reasons. For other grids, [`PatternDB`] takes its patterns at
+//! runtime. (e.g., `PatternDB::< 4, 4 >::build( &[&[1,2,3,4,5], &[6,7,8,9,10],
+//! &[11,12,13,14,15]] )` for the 15-puzzle)
 
 mod patterns;
 mod packings;
 mod db;
 mod helpers;
+mod grid_db;
 
 pub use db::{Pattern6Puzzle, DefaultPatternDB};
+pub use grid_db::{PatternDB, WeightedPatternDB, MAX_PATTERN_SIZE, MAX_NUM_PATTERNS};
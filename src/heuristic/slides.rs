@@ -0,0 +1,66 @@
+// local imports
+use crate::grid::{Goal, PuzzleCell, PuzzleGrid, PuzzleTile};
+use super::general::{Heuristic, SlideHeuristic};
+
+
+/// Turns a (single-tile) [`Heuristic`] into a [`SlideHeuristic`]. A slide moves
+/// at most `max(WIDTH, HEIGHT) - 1` tiles, so it replaces at most that many
+/// single-tile moves. Hence, we divide by that (rounding up), which remains
+/// admissible.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SlideBound< H >( pub H );
+
+impl< H, const WIDTH: usize, const HEIGHT: usize > SlideHeuristic< WIDTH, HEIGHT > for SlideBound< H >
+  where H: Heuristic< WIDTH, HEIGHT >
+{
+  #[inline]
+  fn compute_slides< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    let max_len = ( WIDTH.max( HEIGHT ) - 1 ).max( 1 ) as u8;
+    self.0.compute( p ).div_ceil( max_len )
+  }
+}
+
+/// The largest horizontal distance of any tile to its goal cell, plus the
+/// largest vertical distance. A horizontal slide moves every tile at most one
+/// cell horizontally (and none vertically), so this is admissible.
+/// 
+/// It is often stronger than [`SlideBound`] with a
+/// [`crate::ManhattanDistance`], and needs no precomputation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaxDisplacement< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: Goal< WIDTH, HEIGHT >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > MaxDisplacement< WIDTH, HEIGHT > {
+  /// The displacement from the solved puzzle. (See [`Goal::solved()`])
+  #[inline]
+  pub const fn new( ) -> Self {
+    MaxDisplacement { goal: Goal::solved( ) }
+  }
+
+  /// The displacement from the given goal.
+  #[inline]
+  pub const fn for_goal( goal: Goal< WIDTH, HEIGHT > ) -> Self {
+    MaxDisplacement { goal }
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > SlideHeuristic< WIDTH, HEIGHT > for MaxDisplacement< WIDTH, HEIGHT > {
+  #[inline]
+  fn compute_slides< P: PuzzleGrid< WIDTH, HEIGHT > >( &self, p: &P ) -> u8 {
+    let mut max_dx = 0u8;
+    let mut max_dy = 0u8;
+
+    for cell_idx in 0..PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS {
+      let cell = PuzzleCell::< WIDTH, HEIGHT >::from( cell_idx as u8 );
+      let tile = p.get_tile( cell );
+
+      if tile != PuzzleTile::GAP {
+        let goal_cell = self.goal.cell( tile );
+        max_dx = max_dx.max( cell.x( ).abs_diff( goal_cell.x( ) ) );
+        max_dy = max_dy.max( cell.y( ).abs_diff( goal_cell.y( ) ) );
+      }
+    }
+    max_dx + max_dy
+  }
+}
@@ -3,6 +3,12 @@ mod grid;
 mod heuristic;
 mod search;
 mod examples;
+pub mod service;
+pub mod verify;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(test)]
+mod testing;
 
 pub use examples::*;
 pub use grid::*;
@@ -0,0 +1,74 @@
+// stdlib imports
+use std::io;
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::time::Duration;
+// external imports
+use puzzle24::{DefaultPatternDB, Pattern6Puzzle};
+use puzzle24::service::{HttpConfig, run_batch, serve};
+
+
+const USAGE: &str =
+"Usage:
+  puzzle24 build-db <pattern0> <pattern1>
+      Builds the default pattern database, and writes it to the two files.
+      (This takes a long time, and a lot of memory)
+  puzzle24 batch <pattern0> <pattern1>
+      Loads the pattern database once, and answers JSON-lines solve requests
+      from stdin on stdout.
+  puzzle24 serve <pattern0> <pattern1> [<port> [<timeout-millis>]]
+      Loads the pattern database once, and answers HTTP requests on
+      localhost. (The default port is 2424, and the timeout 30 seconds)";
+
+fn main( ) -> ExitCode {
+  let args: Vec< String > = std::env::args( ).skip( 1 ).collect( );
+  let args: Vec< &str > = args.iter( ).map( String::as_str ).collect( );
+
+  let result =
+    match args[..] {
+      ["build-db", pattern0, pattern1] => {
+        let db0 = Pattern6Puzzle::DEFAULT_PATTERN0.build_pattern_db( );
+        let db1 = Pattern6Puzzle::DEFAULT_PATTERN1.build_pattern_db( );
+        DefaultPatternDB::new( db0, db1 ).save( pattern0, pattern1 )
+      },
+      ["batch", pattern0, pattern1] => {
+        DefaultPatternDB::load( pattern0, pattern1 )
+          .and_then( |db| run_batch( &db, io::stdin( ).lock( ), io::stdout( ).lock( ) ) )
+      },
+      ["serve", pattern0, pattern1, ref rest @ ..] if rest.len( ) <= 2 => {
+        let port = rest.first( ).map_or( Ok( 2424 ), |p| p.parse::< u16 >( ) );
+        let timeout = rest.get( 1 ).map( |t| t.parse::< u64 >( ) ).transpose( );
+
+        match (port, timeout) {
+          (Ok( port ), Ok( timeout )) => {
+            let mut config = HttpConfig { log: |msg| eprintln!( "{}", msg ), ..HttpConfig::default( ) };
+            if let Some( timeout ) = timeout {
+              config.request_timeout = Duration::from_millis( timeout );
+            }
+
+            DefaultPatternDB::load( pattern0, pattern1 ).and_then( |db| {
+              let listener = TcpListener::bind( ( "127.0.0.1", port ) )?;
+              eprintln!( "Listening on {}", listener.local_addr( )? );
+              serve( &db, &listener, &config )
+            } )
+          },
+          _ => {
+            eprintln!( "{}", USAGE );
+            return ExitCode::FAILURE;
+          }
+        }
+      },
+      _ => {
+        eprintln!( "{}", USAGE );
+        return ExitCode::FAILURE;
+      }
+    };
+
+  match result {
+    Ok( () ) => ExitCode::SUCCESS,
+    Err( err ) => {
+      eprintln!( "Error: {}", err );
+      ExitCode::FAILURE
+    }
+  }
+}
@@ -0,0 +1,908 @@
+//! IDA* search, and its variants. The search never copies the puzzle. It keeps
+//! a single grid, on which it makes and undoes moves, and every frame of its
+//! stack only holds the next move and the heuristic. Heuristics may update
+//! incrementally after a move. (See [`Heuristic::compute_moved()`]) Likewise,
+//! every [`Puzzle`] keeps the Zobrist hash of its grid up to date, which keys
+//! the transposition tables, and makes sets of puzzles cheap. (See
+//! [`Puzzle::zobrist_hash()`])
+//!
+//! A heuristic which may be *inconsistent* (i.e., a move may decrease it by
+//! more than 1, as with the pattern databases) says so with
+//! [`Heuristic::is_consistent()`]. Then, [`solve_ita_bpmx()`] (or
+//! [`solve_ida_problem_bpmx()`]) applies bidirectional pathmax: a parent's
+//! bound less the move cost bounds its children, and a child's bound less the
+//! cost bounds its parent, which backtracks as soon as that exceeds the IDA*
+//! bound. The other searches use the estimates as they are.
+
+// stdlib imports
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+// local imports
+use crate::grid::PuzzleGrid;
+use crate::heuristic::{CostHeuristic, Heuristic};
+use crate::grid::{Board, Dir, Goal, LabelGoal, PartialGoal, TileCosts};
+use crate::{PuzzleCell, PuzzleTile};
+
+mod ordering;
+mod problem;
+mod pruning;
+mod slides;
+mod transposition;
+
+pub use ordering::MoveOrdering;
+pub use problem::{ProblemHeuristic, SearchProblem, SlidingPuzzle, WeightedSlidingPuzzle, solve_ida_problem, solve_ida_problem_bpmx, solve_ida_problem_ordered, solve_ida_problem_pruned, solve_ida_problem_table};
+pub use pruning::PruningFsm;
+pub use slides::{Slide, group_slides, solve_ita_slides};
+pub use transposition::{HashedProblem, TranspositionTable};
+
+use ordering::{FixedOrder, SortedOrder};
+use problem::DfsOptions;
+use transposition::NoTable;
+
+
+pub fn solve_ita< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G ) -> (usize, Vec< Dir >) {
+  solve_ita_goal( h, p, &Goal::solved( ) )
+}
+
+pub fn solve_dfs< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, max_depth: u8 ) -> (usize, Option< Vec< Dir > >) {
+  solve_dfs_goal( h, p, &Goal::solved( ), max_depth )
+}
+
+/// Like [`solve_ita`], but solves the puzzle to the given `goal`. The
+/// heuristic must be admissible for that goal. (e.g., see
+/// [`crate::ManhattanDistance::for_goal()`])
+pub fn solve_ita_goal< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT > ) -> (usize, Vec< Dir >) {
+  let mut stack = DfsStack::uninit( );
+  let num_steps = solve_ita_state( &mut stack, h, p, goal );
+  let path = stack.path( );
+  (num_steps, path)
+}
+
+/// Like [`solve_dfs`], but solves the puzzle to the given `goal`. (See
+/// [`solve_ita_goal`])
+pub fn solve_dfs_goal< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, max_depth: u8 ) -> (usize, Option< Vec< Dir > >) {
+  let mut stack = DfsStack::uninit( );
+  let (is_solved, num_steps) = solve_dfs_state_goal( &mut stack, h, p, goal, max_depth );
+  let path =
+    if is_solved {
+      Some( stack.path( ) )
+    } else {
+      None
+    };
+  (num_steps, path)
+}
+
+/// Like [`solve_ita`], but gives up once the `budget` is exhausted.
+pub fn solve_ita_budget< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  solve_ita_budget_goal( h, p, &Goal::solved( ), budget )
+}
+
+/// Like [`solve_dfs`], but gives up once the `budget` is exhausted.
+pub fn solve_dfs_budget< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, max_depth: u8, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  solve_dfs_budget_goal( h, p, &Goal::solved( ), max_depth, budget )
+}
+
+/// Like [`solve_ita_goal`], but gives up once the `budget` is exhausted.
+pub fn solve_ita_budget_goal< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  // All solutions have the same parity (see `crate::validate_rows`), so the
+  // bound increases by 2
+  solve_ita_budget_with( h, p, goal, &PruningFsm::SLIDING_INVERSES, 2, budget )
+}
+
+/// Like [`solve_ita_budget_goal`], but prunes the paths which the automaton
+/// rejects, rather than only inverse moves. (e.g., see
+/// [`PruningFsm::learn_sliding()`]) Its operators are the moves of the gap.
+/// (See [`SlidingPuzzle`])
+pub fn solve_ita_pruned< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, fsm: &PruningFsm, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  assert!( fsm.num_ops( ) == Dir::ALL.len( ), "the automaton must be over the moves of the gap" );
+  solve_ita_budget_with( h, p, goal, fsm, 2, budget )
+}
+
+/// Like [`solve_ita_pruned`], but skips the puzzles which the `table`
+/// remembers, and raises their heuristic. (See [`TranspositionTable`]) The
+/// table forgets its earlier puzzles first.
+/// 
+/// Every IDA* iteration takes as bound the smallest estimated cost that
+/// exceeded the previous bound, as the table may raise it by more than 2.
+/// Note that solutions with more than 255 moves are not found.
+pub fn solve_ita_table< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, fsm: &PruningFsm, table: &mut TranspositionTable, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  assert!( fsm.num_ops( ) == Dir::ALL.len( ), "the automaton must be over the moves of the gap" );
+  let mut stack = DfsStack::uninit( );
+  table.clear( );
+  let (result, stats) = problem::solve_ida_problem_state( &mut stack, &SlidingPuzzle::new( goal ), h, Puzzle::new( p ), &mut DfsOptions { fsm, table, order: &mut FixedOrder, is_bpmx: false }, budget );
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.path( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// Like [`solve_ita_pruned`], but propagates the bounds of an inconsistent
+/// heuristic between parents and children. (i.e., bidirectional pathmax, or
+/// BPMX; see [`solve_ida_problem_bpmx`] and [`Heuristic::is_consistent()`])
+/// Its estimates are then recomputed for every move, rather than by
+/// [`Heuristic::compute_moved()`]. For a consistent heuristic, this is
+/// [`solve_ita_pruned`].
+/// 
+/// Every IDA* iteration takes as bound the smallest estimated cost that
+/// exceeded the previous bound. Note that solutions with more than 255 moves
+/// are not found.
+pub fn solve_ita_bpmx< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, fsm: &PruningFsm, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  assert!( fsm.num_ops( ) == Dir::ALL.len( ), "the automaton must be over the moves of the gap" );
+  let mut stack = DfsStack::uninit( );
+  let (result, stats) = problem::solve_ida_problem_state( &mut stack, &SlidingPuzzle::new( goal ), h, Puzzle::new( p ), &mut DfsOptions { fsm, table: &mut NoTable, order: &mut FixedOrder, is_bpmx: true }, budget );
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.path( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// Like [`solve_ita_pruned`], but tries the moves of the gap from every puzzle
+/// in the given order. (See [`MoveOrdering`])
+/// 
+/// Every IDA* iteration takes as bound the smallest estimated cost that
+/// exceeded the previous bound. (Which, by parity, increases it by 2) Note
+/// that solutions with more than 255 moves are not found.
+pub fn solve_ita_ordered< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, fsm: &PruningFsm, ordering: MoveOrdering, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  assert!( fsm.num_ops( ) == Dir::ALL.len( ), "the automaton must be over the moves of the gap" );
+  let problem = SlidingPuzzle::new( goal );
+  let mut stack = DfsStack::uninit( );
+  let (result, stats) =
+    if ordering == MoveOrdering::Fixed {
+      problem::solve_ida_problem_state( &mut stack, &problem, h, Puzzle::new( p ), &mut DfsOptions { fsm, table: &mut NoTable, order: &mut FixedOrder, is_bpmx: false }, budget )
+    } else {
+      let mut order = SortedOrder::new( ordering, Dir::ALL.len( ), fsm );
+      problem::solve_ida_problem_state( &mut stack, &problem, h, Puzzle::new( p ), &mut DfsOptions { fsm, table: &mut NoTable, order: &mut order, is_bpmx: false }, budget )
+    };
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.path( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// Like [`solve_ita_budget_goal`], but stops at the first puzzle which matches
+/// the partial `goal`. (i.e., where all cared-about tiles are in their cells)
+/// The heuristic must be admissible for that partial goal. (e.g., see
+/// [`crate::PatternDB::build_partial()`])
+/// 
+/// Unless the gap is cared about, solutions of either parity exist. So, every
+/// IDA* iteration increases the bound by 1.
+pub fn solve_ita_partial< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &PartialGoal< WIDTH, HEIGHT >, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  solve_ita_budget_with( h, p, goal, &PruningFsm::SLIDING_INVERSES, 1, budget )
+}
+
+/// Like [`solve_ita_budget_goal`], but tiles with the same label are
+/// interchangeable. It stops at the first puzzle where every cell contains a
+/// tile with the goal's label. The heuristic must be admissible for that goal.
+/// (e.g., see [`crate::MatchingDistance`])
+/// 
+/// If several tiles share a label, solutions of either parity exist. Then,
+/// every IDA* iteration increases the bound by 1.
+pub fn solve_ita_labels< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &LabelGoal< WIDTH, HEIGHT >, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  let depth_step = if goal.has_duplicates( ) { 1 } else { 2 };
+  solve_ita_budget_with( h, p, goal, &PruningFsm::SLIDING_INVERSES, depth_step, budget )
+}
+
+/// IDA* with the pruning automaton, where every iteration increases the bound
+/// by `depth_step`.
+fn solve_ita_budget_with< H: Heuristic< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &T, fsm: &PruningFsm, depth_step: u8, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  let mut stack = DfsStack::uninit( );
+  let mut meter = BudgetMeter::new( budget );
+  let mut stats = SearchStats::default( );
+
+  let outcome =
+    if goal.is_goal( &p ) { // it's solved already
+      SearchOutcome::Solved( Vec::new( ) )
+    } else {
+      let mut max_depth: u8 = 1;
+
+      loop {
+        let (result, num_steps) = solve_dfs_state_budget( &mut stack, h, p.clone( ), goal, fsm, max_depth, &mut meter );
+        stats.num_iterations += 1;
+        stats.num_last_steps = num_steps;
+
+        match result {
+          DfsResult::Solved => break SearchOutcome::Solved( stack.path( ) ),
+          DfsResult::OutOfBudget => break SearchOutcome::OutOfBudget,
+          DfsResult::Exhausted => { },
+        }
+
+        // Paths never exceed 255 steps (see `DfsStack::uninit`)
+        match max_depth.checked_add( depth_step ) {
+          Some( d ) => max_depth = d,
+          None => break SearchOutcome::Exhausted,
+        }
+      }
+    };
+
+  stats.num_steps = meter.num_steps;
+  stats.elapsed = meter.elapsed( );
+  (outcome, stats)
+}
+
+/// Like [`solve_dfs_goal`], but gives up once the `budget` is exhausted.
+pub fn solve_dfs_budget_goal< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, max_depth: u8, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  let mut stack = DfsStack::uninit( );
+  let mut meter = BudgetMeter::new( budget );
+
+  let outcome =
+    if goal.is_goal( &p ) { // it's solved already
+      SearchOutcome::Solved( Vec::new( ) )
+    } else {
+      let (result, _) = solve_dfs_state_budget( &mut stack, h, p, goal, &PruningFsm::SLIDING_INVERSES, max_depth, &mut meter );
+
+      match result {
+        DfsResult::Solved => SearchOutcome::Solved( stack.path( ) ),
+        DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+        DfsResult::Exhausted => SearchOutcome::Exhausted,
+      }
+    };
+
+  let stats = SearchStats { num_steps: meter.num_steps, num_iterations: 1, num_last_steps: meter.num_steps, elapsed: meter.elapsed( ) };
+  (outcome, stats)
+}
+
+/// Like [`solve_ita_budget_goal`], but minimises the total cost of the moves,
+/// where moving a tile costs [`TileCosts::cost()`]. The heuristic must be
+/// admissible for those costs. (e.g., see [`crate::WeightedPatternDB`])
+/// 
+/// Rather than increasing the bound by 2, every IDA* iteration takes as bound
+/// the smallest estimated cost that exceeded the previous bound. Note that
+/// solutions with more than 255 moves are not found.
+pub fn solve_ita_weighted< H: CostHeuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT >, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  solve_ita_board( h, p, goal, &Board::open( ), costs, budget )
+}
+
+/// Like [`solve_ita_weighted`], but the gap never moves into the blocked cells
+/// of the `board`, and wraps around its edges on a torus. (For regular moves,
+/// pass [`TileCosts::unit()`]) The heuristic must be admissible on that board.
+/// (e.g., see [`crate::BoardDistance`], [`crate::TorusManhattanDistance`] and
+/// [`crate::PatternDB::build_on_board()`])
+/// 
+/// If the goal is unreachable, the search never ends. (See
+/// [`Board::validate_rows()`])
+pub fn solve_ita_board< H: CostHeuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT >, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  let problem = WeightedSlidingPuzzle::new( goal, board, costs );
+  let mut stack = DfsStack::uninit( );
+  let (result, stats) = problem::solve_ida_problem_state( &mut stack, &problem, h, Puzzle::new( p ), &mut DfsOptions { fsm: &PruningFsm::SLIDING_INVERSES, table: &mut NoTable, order: &mut FixedOrder, is_bpmx: false }, budget );
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.path( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// Limits on the resources spent by a search. `None` means unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Budget {
+  /// The maximum number of generated nodes (summed over all iterations)
+  pub max_steps: Option< usize >,
+  /// The maximum wall-clock time
+  pub max_time: Option< Duration >,
+}
+
+/// The result of a search under a [`Budget`]. (For a [`SearchProblem`], the
+/// solution contains its operators)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SearchOutcome< O = Dir > {
+  /// The directions in which the tiles move. (As returned by [`solve_ita`])
+  Solved( Vec< O > ),
+  /// The search space was fully explored without finding a solution. (With
+  /// [`solve_dfs_budget`], within the given maximum depth)
+  Exhausted,
+  /// The budget ran out before a solution was found.
+  OutOfBudget,
+}
+
+/// Statistics on a (completed or aborted) search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchStats {
+  /// The number of generated nodes
+  pub num_steps: usize,
+  /// The number of depth-first iterations (i.e., of IDA*)
+  pub num_iterations: usize,
+  /// The number of generated nodes in the last iteration. (Which stops at the
+  /// first solution, so it depends on the [`MoveOrdering`])
+  pub num_last_steps: usize,
+  pub elapsed: Duration,
+}
+
+/// Tracks a [`Budget`] during a search.
+struct BudgetMeter {
+  max_steps: usize,
+  deadline: Option< Instant >,
+  start: Instant,
+  /// The number of steps in preceding iterations
+  num_steps: usize,
+}
+
+impl BudgetMeter {
+  /// We only consult the clock once every so many steps, as it's relatively
+  /// expensive.
+  const CLOCK_INTERVAL: usize = 4096;
+
+  fn new( budget: Budget ) -> Self {
+    let start = Instant::now( );
+    BudgetMeter {
+      max_steps: budget.max_steps.unwrap_or( usize::MAX ),
+      deadline: budget.max_time.map( |t| start + t ),
+      start,
+      num_steps: 0
+    }
+  }
+
+  /// Returns `true` if the search may continue after taking `num_steps` steps
+  /// in the current iteration.
+  #[inline]
+  fn allows( &self, num_steps: usize ) -> bool {
+    let total_steps = self.num_steps + num_steps;
+
+    if total_steps > self.max_steps {
+      return false;
+    }
+
+    match self.deadline {
+      Some( deadline ) if total_steps.is_multiple_of( Self::CLOCK_INTERVAL ) => Instant::now( ) < deadline,
+      _ => true,
+    }
+  }
+
+  fn elapsed( &self ) -> Duration {
+    self.start.elapsed( )
+  }
+}
+
+/// A puzzle on a `WIDTH`x`HEIGHT` grid, which tracks the cell of its gap and
+/// the Zobrist hash of its grid. Moves update the hash in constant time. (See
+/// [`Puzzle::zobrist_hash()`])
+/// 
+/// Invariants:
+/// * grid[ gap_cell ] contains tile 0
+/// * hash == grid.zobrist_hash( )
+#[derive(Clone, PartialEq, Eq)]
+pub struct Puzzle< G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  grid: G,
+  gap_cell: PuzzleCell< WIDTH, HEIGHT >,
+  hash: u64
+}
+
+/// A 24-puzzle.
+pub type Puzzle24< G > = Puzzle< G, 5, 5 >;
+
+impl< G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > fmt::Debug for Puzzle< G, WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.grid.fmt( f )
+  }
+}
+
+/// Hashes only the Zobrist hash, rather than the grid. (So, sets of puzzles
+/// are cheap)
+impl< G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > Hash for Puzzle< G, WIDTH, HEIGHT > {
+  #[inline]
+  fn hash< S: Hasher >( &self, state: &mut S ) {
+    state.write_u64( self.hash );
+  }
+}
+
+/// Serialised as its grid. (The gap cell and hash are recomputed on
+/// deserialisation)
+#[cfg(feature = "serde")]
+impl< G: PuzzleGrid< WIDTH, HEIGHT > + serde::Serialize, const WIDTH: usize, const HEIGHT: usize > serde::Serialize for Puzzle< G, WIDTH, HEIGHT > {
+  fn serialize< S: serde::Serializer >( &self, serializer: S ) -> Result< S::Ok, S::Error > {
+    self.grid.serialize( serializer )
+  }
+}
+
+#[cfg(feature = "serde")]
+impl< 'de, G: PuzzleGrid< WIDTH, HEIGHT > + serde::Deserialize< 'de >, const WIDTH: usize, const HEIGHT: usize > serde::Deserialize< 'de > for Puzzle< G, WIDTH, HEIGHT > {
+  fn deserialize< D: serde::Deserializer< 'de > >( deserializer: D ) -> Result< Self, D::Error > {
+    G::deserialize( deserializer ).map( Puzzle::new )
+  }
+}
+
+impl< G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > Puzzle< G, WIDTH, HEIGHT > {
+  pub fn new( grid: G ) -> Self {
+    let gap_cell = grid.find_gap( );
+    let hash = grid.zobrist_hash( );
+    Puzzle { grid, gap_cell, hash }
+  }
+
+  #[inline]
+  pub fn grid( &self ) -> &G {
+    &self.grid
+  }
+
+  /// Returns the Zobrist hash of the grid, which is kept up to date by every
+  /// move. (See [`PuzzleGrid::zobrist_hash()`]) Equal grids have equal hashes,
+  /// whichever moves reached them.
+  #[inline]
+  pub fn zobrist_hash( &self ) -> u64 {
+    self.hash
+  }
+
+  #[inline]
+  pub fn step_inv( &self, inv_dir: Dir ) -> Option< Self > {
+    let adj_cell = self.gap_cell.step( inv_dir )?;
+    Some( self.move_gap( adj_cell ) )
+  }
+
+  /// Like [`Puzzle::step_inv()`], but on a board with blocked cells (which the
+  /// gap never moves into) and its topology. (See [`Board::step()`])
+  #[inline]
+  pub fn step_inv_on( &self, inv_dir: Dir, board: &Board< WIDTH, HEIGHT > ) -> Option< Self > {
+    let adj_cell = board.step( self.gap_cell, inv_dir )?;
+    Some( self.move_gap( adj_cell ) )
+  }
+
+  /// Like [`Puzzle::step_inv()`], but moves the gap in place. Returns `false`
+  /// (leaving the puzzle unchanged) if the gap cannot move in `inv_dir`.
+  #[inline]
+  pub fn step_inv_mut( &mut self, inv_dir: Dir ) -> bool {
+    match self.gap_cell.step( inv_dir ) {
+      Some( adj_cell ) => { self.move_gap_mut( adj_cell ); true },
+      None => false,
+    }
+  }
+
+  /// Like [`Puzzle::step_inv_on()`], but moves the gap in place. (See
+  /// [`Puzzle::step_inv_mut()`])
+  #[inline]
+  pub fn step_inv_on_mut( &mut self, inv_dir: Dir, board: &Board< WIDTH, HEIGHT > ) -> bool {
+    match board.step( self.gap_cell, inv_dir ) {
+      Some( adj_cell ) => { self.move_gap_mut( adj_cell ); true },
+      None => false,
+    }
+  }
+
+  /// Moves the gap into the adjacent cell, in place.
+  #[inline]
+  fn move_gap_mut( &mut self, adj_cell: PuzzleCell< WIDTH, HEIGHT > ) {
+    debug_assert!( self.grid.get_tile( self.gap_cell ) == PuzzleTile::GAP );
+
+    // The tile we move into the gap
+    let moved_tile = self.grid.get_tile( adj_cell );
+    debug_assert!( moved_tile != PuzzleTile::GAP, "{:?}", self );
+
+    self.grid.set_gap_tile_unchecked( self.gap_cell, moved_tile );
+    self.grid.clear_cell( adj_cell );
+    self.hash ^= moved_tile.zobrist_key( adj_cell ) ^ moved_tile.zobrist_key( self.gap_cell );
+    self.gap_cell = adj_cell;
+  }
+
+  /// Moves the gap into the adjacent cell.
+  #[inline]
+  fn move_gap( &self, adj_cell: PuzzleCell< WIDTH, HEIGHT > ) -> Self {
+    debug_assert!( self.grid.get_tile( self.gap_cell ) == PuzzleTile::GAP );
+
+    // The tile we move into the gap
+    let moved_tile = self.grid.get_tile( adj_cell );
+    
+    let mut new_grid = self.grid.clone( );
+    new_grid.set_tile( self.gap_cell, moved_tile );
+    new_grid.clear_cell( adj_cell );
+    let hash = self.hash ^ moved_tile.zobrist_key( adj_cell ) ^ moved_tile.zobrist_key( self.gap_cell );
+    let new_puzzle = Puzzle { grid: new_grid, gap_cell: adj_cell, hash };
+    debug_assert!( moved_tile != PuzzleTile::GAP, "{:?}", new_puzzle );
+
+    new_puzzle
+  }
+}
+
+
+fn solve_ita_state< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( stack: &mut DfsStack< G, WIDTH, HEIGHT >, h: &H, p: G, goal: &Goal< WIDTH, HEIGHT > ) -> usize {
+  let mut num_steps_total = 0;
+  let mut max_depth = 1;
+
+  loop {
+    // println!( "Now at depth {}", max_depth );
+    let (is_solved, num_steps) = solve_dfs_state_goal( stack, h, p.clone( ), goal, max_depth );
+    num_steps_total += num_steps;
+
+    if is_solved {
+      return num_steps_total;
+    }
+
+    max_depth += 2;
+  }
+}
+
+#[inline]
+pub fn solve_dfs_state< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( stack: &mut DfsStack< G, WIDTH, HEIGHT >, h: &H, p: G, max_depth: u8 ) -> (bool, usize) {
+  solve_dfs_state_goal( stack, h, p, &Goal::solved( ), max_depth )
+}
+
+/// Like [`solve_dfs_state`], but solves the puzzle to the given `goal`.
+#[inline]
+pub fn solve_dfs_state_goal< H: Heuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( stack: &mut DfsStack< G, WIDTH, HEIGHT >, h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, max_depth: u8 ) -> (bool, usize) {
+  if goal.is_goal( &p ) { // it's solved already
+    stack.init( Puzzle::new( p ) );
+    return (true, 0);
+  }
+  let mut meter = BudgetMeter::new( Budget::default( ) );
+  let (result, num_steps) = solve_dfs_state_budget( stack, h, p, goal, &PruningFsm::SLIDING_INVERSES, max_depth, &mut meter );
+  (result == DfsResult::Solved, num_steps)
+}
+
+/// The puzzles at which a search stops. (e.g., a [`Goal`], [`PartialGoal`] or
+/// [`LabelGoal`])
+pub trait GoalTest< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  fn is_goal< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> bool;
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > GoalTest< WIDTH, HEIGHT > for Goal< WIDTH, HEIGHT > {
+  #[inline]
+  fn is_goal< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> bool {
+    Goal::is_goal( self, grid )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > GoalTest< WIDTH, HEIGHT > for PartialGoal< WIDTH, HEIGHT > {
+  #[inline]
+  fn is_goal< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> bool {
+    PartialGoal::is_goal( self, grid )
+  }
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > GoalTest< WIDTH, HEIGHT > for LabelGoal< WIDTH, HEIGHT > {
+  #[inline]
+  fn is_goal< G: PuzzleGrid< WIDTH, HEIGHT > >( &self, grid: &G ) -> bool {
+    LabelGoal::is_goal( self, grid )
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DfsResult {
+  Solved,
+  Exhausted,
+  OutOfBudget
+}
+
+#[inline]
+fn solve_dfs_state_budget< H: Heuristic< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( stack: &mut DfsStack< G, WIDTH, HEIGHT >, h: &H, p: G, goal: &T, fsm: &PruningFsm, max_depth: u8, meter: &mut BudgetMeter ) -> (DfsResult, usize) {
+  let prev_num_steps = meter.num_steps;
+  stack.init( Puzzle::new( p ) );
+  let (result, _) = problem::solve_dfs_problem( stack, &SlidingPuzzle::new( goal ), h, &mut DfsOptions { fsm, table: &mut NoTable, order: &mut FixedOrder, is_bpmx: false }, max_depth as u32, meter );
+  (result, meter.num_steps - prev_num_steps)
+}
+
+/// The stack of a depth-first search on a [`SearchProblem`], with states `S`
+/// and operators `O`. It keeps a single state, to which the search applies
+/// operators in place, and undoes them when it backtracks. (See
+/// [`SearchProblem::apply()`])
+///
+/// Invariant: frames.len( ) == ops.len( ) + 1 (during a search)
+pub struct SearchStack< S, O > {
+  /// The state at the top of the stack. (`None` before [`SearchStack::init()`])
+  state: Option< S >,
+  frames: Vec< Frame >,
+  /// The applied operators
+  ops: Vec< O >,
+}
+
+/// The stack of the sliding puzzle solvers. Its operators are the moves of the
+/// gap. (See [`SlidingPuzzle`])
+pub type DfsStack< G, const WIDTH: usize = 5, const HEIGHT: usize = 5 > = SearchStack< Puzzle< G, WIDTH, HEIGHT >, Dir >;
+
+/// A frame on the [`SearchStack`]. For the state at that depth, it contains
+/// the index of the next operator to explore (see [`SearchProblem::ops()`]),
+/// the state of the [`PruningFsm`], the heuristic, and the path cost.
+pub type Frame = (u8, u16, u32, u32);
+
+impl< S, O: Copy > SearchStack< S, O > {
+  pub fn uninit( ) -> Self {
+    // We represent path lengths by `u8`, because paths can never be longer than
+    // `255`. (Actually, less than that, TODO)
+    SearchStack { state: None, frames: Vec::with_capacity( 256 ), ops: Vec::with_capacity( 255 ) }
+  }
+
+  /// Returns the state at the top of the stack. (e.g., the goal state, after a
+  /// solved search)
+  pub fn state( &self ) -> Option< &S > {
+    self.state.as_ref( )
+  }
+
+  /// Returns the operators applied to the initial state, up to the top.
+  pub fn ops( &self ) -> &[O] {
+    &self.ops
+  }
+
+  /// Empties the stack, with the initial state of the next search.
+  pub fn init( &mut self, state: S ) {
+    self.frames.clear( );
+    self.ops.clear( );
+    self.state = Some( state );
+  }
+
+  /// Pushes the root frame, with the initial state's heuristic.
+  #[inline]
+  fn init_frames( &mut self, hx: u32 ) {
+    self.frames.clear( );
+    self.ops.clear( );
+    self.frames.push( ( 0, 0, hx, 0 ) );
+  }
+
+  #[inline]
+  pub fn top_mut( &mut self ) -> Option< (Option< O >, &mut Frame) > {
+    if self.frames.is_empty( ) {
+      None
+    } else {
+      let top_op = self.top_op( );
+      Some( ( top_op, unsafe { self.top_unchecked_mut( ) } ) )
+    }
+  }
+
+  /// Returns the last applied operator, if any.
+  #[inline]
+  pub fn top_op( &self ) -> Option< O > {
+    self.ops.last( ).copied( )
+  }
+
+  /// # Safety
+  ///
+  /// The stack must be non-empty.
+  #[inline]
+  pub unsafe fn top_unchecked_mut( &mut self ) -> &mut Frame {
+    debug_assert!( !self.frames.is_empty( ) );
+    let frames_len = self.frames.len( );
+    self.frames.get_unchecked_mut( frames_len - 1 )
+  }
+
+  /// Pushes the frame of the state reached by applying `op`, with automaton
+  /// state `fsm_state` and heuristic `hx` at path cost `cost`.
+  ///
+  /// # Safety
+  ///
+  /// The stack must not be at its capacity. (i.e., its depth must not exceed
+  /// `255`)
+  #[inline]
+  pub unsafe fn push_unchecked( &mut self, op: O, fsm_state: u16, hx: u32, cost: u32 ) {
+    // The same as: `self.frames.push( (0, fsm_state, hx, cost) )`
+    debug_assert!( self.frames.len( ) < self.frames.capacity( ) );
+    let ptr = self.frames.as_mut_ptr( ).add( self.frames.len( ) );
+    ptr.write( ( 0, fsm_state, hx, cost ) );
+    self.frames.set_len( self.frames.len( ) + 1 );
+
+    // The same as: `self.ops.push( op )`
+    debug_assert!( self.ops.len( ) < self.ops.capacity( ) );
+    let ptr = self.ops.as_mut_ptr( ).add( self.ops.len( ) );
+    ptr.write( op );
+    self.ops.set_len( self.ops.len( ) + 1 );
+  }
+
+  /// # Safety
+  ///
+  /// The stack must be non-empty.
+  #[inline]
+  pub unsafe fn pop( &mut self ) {
+    self.ops.pop( );
+    debug_assert!( !self.frames.is_empty( ) );
+    self.frames.set_len( self.frames.len( ) - 1 );
+  }
+}
+
+impl< G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > SearchStack< Puzzle< G, WIDTH, HEIGHT >, Dir > {
+  /// The path with "inverted" directions. (i.e., it represents the movements
+  /// of the gap)
+  pub fn inv_path( &self ) -> &[Dir] {
+    &self.ops
+  }
+
+  /// Returns the directions in which the tiles moved. (i.e., the inverse of
+  /// [`DfsStack::inv_path()`])
+  pub fn path( &self ) -> Vec< Dir > {
+    self.ops.iter( ).map( |x| x.inv( ) ).collect( )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  // stdlib imports
+  use std::collections::HashSet;
+  // local imports
+  use crate::grid::{ArrayGrid, Board, Goal, PackedGrid};
+  use crate::heuristic::{ManhattanDistance, MatchingDistance, PatternDB};
+  use crate::testing::{all_grids, apply_path, bfs_distances, bfs_distances_from, random_grids};
+  use crate::verify::SplitMix64;
+  use super::*;
+
+  #[test]
+  fn ita_finds_shortest_paths( ) {
+    // Every solvable 2x3 grid
+    let goal = Goal::< 2, 3 >::solved( );
+    for (p, distance) in bfs_distances( &goal, &Board::open( ) ) {
+      let (_, path) = solve_ita( &ManhattanDistance::new( ), p );
+      assert_eq!( path.len( ), distance as usize, "{:?}", p );
+      assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+    }
+
+    let goal = Goal::< 3, 3 >::solved( );
+    let distances = bfs_distances( &goal, &Board::open( ) );
+    for p in random_grids( &goal, 30, 60, 2 ) {
+      let (_, path) = solve_ita( &ManhattanDistance::new( ), p );
+      assert_eq!( path.len( ), distances[ &p ] as usize, "{:?}", p );
+      assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+    }
+  }
+
+  /// Checks the searches to the goal, with heuristics built for it, against
+  /// BFS. (On every grid which reaches the goal, or on `num_samples` of them)
+  fn check_goal< const WIDTH: usize, const HEIGHT: usize >( goal: &Goal< WIDTH, HEIGHT >, patterns: &[&[u8]], num_samples: Option< usize > ) {
+    let distances = bfs_distances( goal, &Board::open( ) );
+    let grids =
+      match num_samples {
+        Some( n ) => random_grids( goal, n, 60, 4 ),
+        None => distances.keys( ).copied( ).collect( ),
+      };
+    let manhattan = ManhattanDistance::for_goal( *goal );
+    let db = PatternDB::build_for_goal( goal, patterns );
+
+    for p in grids {
+      let distance = distances[ &p ] as usize;
+      assert!( manhattan.compute( &p ) as usize <= distance && db.compute( &p ) as usize <= distance, "{:?}", p );
+
+      let (_, path) = solve_ita_goal( &manhattan, p, goal );
+      assert_eq!( path.len( ), distance, "{:?}", p );
+      assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+
+      let SearchOutcome::Solved( path ) = solve_ita_budget_goal( &db, p, goal, Budget::default( ) ).0 else {
+        panic!( "{:?} is unsolved", p );
+      };
+      assert_eq!( path.len( ), distance, "{:?}", p );
+      assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+    }
+  }
+
+  #[test]
+  fn goals_are_reached_optimally( ) {
+    check_goal( &Goal::< 2, 3 >::gap_last( ), &[ &[1,2,3], &[4,5] ], None );
+    check_goal( &Goal::< 3, 2 >::snake( ), &[ &[1,2,3,4,5] ], None );
+    check_goal( &Goal::< 2, 3 >::from_rows( &[[3, 5], [0, 1], [4, 2]] ).unwrap( ), &[ &[1,2], &[3,4,5] ], None );
+    check_goal( &Goal::< 3, 3 >::snake( ), &[ &[1,2,3,4], &[5,6,7,8] ], Some( 20 ) );
+  }
+
+  #[test]
+  fn partial_goals_are_reached_optimally( ) {
+    let goal = Goal::< 4, 2 >::solved( );
+    let grids = all_grids::< 4, 2 >( );
+    let partial_goals = [
+      PartialGoal::first_rows( goal, 1 ),
+      PartialGoal::new( goal, &[2, 5, 7] ),
+      // With the gap
+      PartialGoal::new( goal, &[0, 1, 6] ),
+    ];
+    let patterns: [&[&[u8]]; 3] = [ &[ &[1], &[2,3] ], &[ &[2,5,7] ], &[ &[1,6] ] ];
+
+    for (partial_goal, patterns) in partial_goals.iter( ).zip( patterns ) {
+      let starts: Vec< ArrayGrid< 4, 2 > > = grids.iter( ).copied( ).filter( |p| partial_goal.is_goal( p ) ).collect( );
+      let distances = bfs_distances_from( &starts, &Board::open( ) );
+      let db = PatternDB::build_partial( partial_goal, patterns );
+
+      // Don't-care tiles give partial goals of either parity, so every grid
+      // (solvable to the goal or not) reaches one
+      assert_eq!( distances.len( ), grids.len( ) );
+      for &p in grids.iter( ).step_by( 997 ) {
+        let distance = distances[ &p ] as usize;
+        assert!( db.compute( &p ) as usize <= distance, "{:?}", p );
+
+        let SearchOutcome::Solved( path ) = solve_ita_partial( &db, p, partial_goal, Budget::default( ) ).0 else {
+          panic!( "{:?} is unsolved", p );
+        };
+        assert_eq!( path.len( ), distance, "{:?}", p );
+        assert!( partial_goal.is_goal( &apply_path( &p, &path ) ) );
+      }
+    }
+  }
+
+  #[test]
+  fn label_goals_are_reached_optimally( ) {
+    let grids = all_grids::< 3, 2 >( );
+    for rows in [ [[0, 1, 1], [2, 2, 1]], [[0, 1, 2], [3, 4, 5]] ] {
+      let goal = LabelGoal::< 3, 2 >::from_rows( &rows ).unwrap( );
+      let starts: Vec< ArrayGrid< 3, 2 > > = grids.iter( ).copied( ).filter( |p| goal.is_goal( p ) ).collect( );
+      let distances = bfs_distances_from( &starts, &Board::open( ) );
+      let h = MatchingDistance::new( goal );
+
+      for (&p, &distance) in &distances {
+        assert!( h.compute( &p ) as u32 <= distance, "{:?}", p );
+
+        let SearchOutcome::Solved( path ) = solve_ita_labels( &h, p, &goal, Budget::default( ) ).0 else {
+          panic!( "{:?} is unsolved", p );
+        };
+        assert_eq!( path.len( ), distance as usize, "{:?}", p );
+        assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+      }
+    }
+  }
+
+  /// Checks the incremental hash after every move of random walks.
+  fn check_zobrist_hash< G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( board: &Board< WIDTH, HEIGHT > ) {
+    let mut rng = SplitMix64( 3 );
+    for _ in 0..10 {
+      let mut p = Puzzle::new( Goal::< WIDTH, HEIGHT >::solved( ).to_grid::< G >( ) );
+      for _ in 0..200 {
+        if p.step_inv_on_mut( Dir::ALL[ ( rng.next( ) % 4 ) as usize ], board ) {
+          assert_eq!( p.zobrist_hash( ), p.grid( ).zobrist_hash( ), "{:?}", p );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn incremental_zobrist_hash( ) {
+    check_zobrist_hash::< ArrayGrid< 3, 3 >, 3, 3 >( &Board::open( ) );
+    check_zobrist_hash::< ArrayGrid< 2, 4 >, 2, 4 >( &Board::open( ) );
+    check_zobrist_hash::< PackedGrid< 4, 4 >, 4, 4 >( &Board::open( ) );
+    check_zobrist_hash::< PackedGrid< 5, 5 >, 5, 5 >( &Board::open( ) );
+    // Moves which wrap around, or avoid blocked cells
+    check_zobrist_hash::< ArrayGrid< 3, 3 >, 3, 3 >( &Board::torus( ) );
+    check_zobrist_hash::< PackedGrid< 4, 4 >, 4, 4 >( &Board::torus( ) );
+    let board = Board::< 3, 3 >::from_rows( &[[false, false, false], [false, true, false], [false, false, true]] );
+    check_zobrist_hash::< ArrayGrid< 3, 3 >, 3, 3 >( &board );
+  }
+
+  #[test]
+  fn zobrist_hashes_are_distinct( ) {
+    // Both representations of a grid have its hash
+    let grids = all_grids::< 3, 3 >( );
+    let hashes: HashSet< u64 > = grids.iter( ).map( |p| p.zobrist_hash( ) ).collect( );
+    assert_eq!( hashes.len( ), grids.len( ) );
+    for p in grids.iter( ).step_by( 101 ) {
+      assert_eq!( PackedGrid::< 3, 3 >::from( p.to_rows( ) ).zobrist_hash( ), p.zobrist_hash( ) );
+    }
+  }
+
+  /// Like [`solve_ita_bpmx`], but tries the moves in the given order.
+  fn solve_ordered_bpmx( h: &PatternDB< 3, 3 >, p: ArrayGrid< 3, 3 >, goal: &Goal< 3, 3 >, ordering: MoveOrdering ) -> SearchOutcome {
+    let fsm = &PruningFsm::SLIDING_INVERSES;
+    let mut order = SortedOrder::new( ordering, Dir::ALL.len( ), fsm );
+    let mut stack = DfsStack::uninit( );
+    let (result, _) = problem::solve_ida_problem_state( &mut stack, &SlidingPuzzle::new( goal ), h, Puzzle::new( p ), &mut DfsOptions { fsm, table: &mut NoTable, order: &mut order, is_bpmx: true }, Budget::default( ) );
+    assert_eq!( result, DfsResult::Solved );
+    SearchOutcome::Solved( stack.path( ) )
+  }
+
+  #[test]
+  fn bpmx_keeps_the_optimal_length( ) {
+    let goal = Goal::solved( );
+    let distances = bfs_distances( &goal, &Board::open( ) );
+    // Gap-agnostic tables, which are inconsistent
+    let h = PatternDB::< 3, 3 >::build( &[ &[1,2,3,4], &[5,6,7,8] ] );
+    assert!( !Heuristic::is_consistent( &h ) );
+
+    for p in random_grids( &goal, 30, 60, 1 ) {
+      let distance = distances[ &p ] as usize;
+      let outcomes = [
+        solve_ita_pruned( &h, p, &goal, &PruningFsm::SLIDING_INVERSES, Budget::default( ) ).0,
+        solve_ita_bpmx( &h, p, &goal, &PruningFsm::SLIDING_INVERSES, Budget::default( ) ).0,
+        solve_ordered_bpmx( &h, p, &goal, MoveOrdering::Heuristic ),
+        solve_ordered_bpmx( &h, p, &goal, MoveOrdering::History ),
+      ];
+
+      for outcome in outcomes {
+        let SearchOutcome::Solved( path ) = outcome else {
+          panic!( "{:?} is unsolved", p );
+        };
+        assert_eq!( path.len( ), distance, "{:?}", p );
+        assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+      }
+    }
+  }
+}
@@ -0,0 +1,215 @@
+//! The order in which the depth-first search tries the children of a state.
+//! Within the last IDA* iteration, the search stops at the first solution, so
+//! trying promising children first saves nodes. (Earlier iterations search
+//! their whole tree, in any order)
+//!
+//! [`crate::solve_ita_ordered()`] and [`crate::solve_ida_problem_ordered()`]
+//! take a [`MoveOrdering`]: the fixed order of the operators, the children
+//! sorted by their estimated cost, or an order learned in earlier iterations.
+//! (From how often every move kept the estimate, after the same previous move)
+//! [`crate::SearchStats::num_last_steps`] counts the nodes of the last
+//! iteration.
+
+// local imports
+use super::{Frame, ProblemHeuristic, PruningFsm, SearchProblem};
+
+
+/// The order in which the search tries the children of every state. (See
+/// [`super::solve_ida_problem_ordered`] and [`super::solve_ita_ordered`]) The
+/// effect shows in [`super::SearchStats::num_last_steps`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveOrdering {
+  /// The order of the operators. (i.e., [`SearchProblem::ops()`])
+  #[default]
+  Fixed,
+  /// By increasing estimated cost of the children. So, every state evaluates
+  /// the heuristic of all its children, before it tries the first.
+  Heuristic,
+  /// By how often the operator did not increase the estimated cost, in earlier
+  /// iterations, after the same automaton state. (e.g., after the same
+  /// previous move)
+  History,
+}
+
+/// Orders the children of the states on the search's path. The search calls
+/// [`ChildOrder::order()`] whenever it pushes a state. (See
+/// [`super::problem::solve_dfs_problem`])
+pub(super) trait ChildOrder< P: SearchProblem > {
+  /// Orders the children of the state, at `depth` on the path, with its
+  /// (just pushed) frame.
+  fn order< H: ProblemHeuristic< P > >( &mut self, problem: &P, h: &H, state: &mut P::State, fsm: &PruningFsm, depth: usize, frame: Frame );
+
+  /// Returns the index of the `i`-th operator to try at `depth`.
+  fn op_idx( &self, depth: usize, i: usize ) -> usize;
+
+  /// The child by the operator is searched, and its estimated cost is `gx`,
+  /// while the state's is `prev_gx`.
+  fn record( &mut self, fsm_state: u16, op_idx: usize, prev_gx: u32, gx: u32 );
+}
+
+/// Tries the operators in order.
+pub(super) struct FixedOrder;
+
+impl< P: SearchProblem > ChildOrder< P > for FixedOrder {
+  #[inline(always)]
+  fn order< H: ProblemHeuristic< P > >( &mut self, _problem: &P, _h: &H, _state: &mut P::State, _fsm: &PruningFsm, _depth: usize, _frame: Frame ) { }
+
+  #[inline(always)]
+  fn op_idx( &self, _depth: usize, i: usize ) -> usize {
+    i
+  }
+
+  #[inline(always)]
+  fn record( &mut self, _fsm_state: u16, _op_idx: usize, _prev_gx: u32, _gx: u32 ) { }
+}
+
+/// Sorts the operators at every depth, by [`MoveOrdering::Heuristic`] or
+/// [`MoveOrdering::History`].
+pub(super) struct SortedOrder {
+  ordering: MoveOrdering,
+  num_ops: usize,
+  /// The operator indices at every depth, in order
+  orders: Vec< u8 >,
+  /// The counts of [`MoveOrdering::History`], indexed by
+  /// `fsm_state * num_ops + op_idx`
+  history: Vec< u32 >,
+  /// The sort keys of the operators, with their indices
+  keys: Vec< (u32, u8) >,
+}
+
+impl SortedOrder {
+  pub(super) fn new( ordering: MoveOrdering, num_ops: usize, fsm: &PruningFsm ) -> Self {
+    let history =
+      if ordering == MoveOrdering::History {
+        vec![ 0; fsm.num_states( ) * num_ops ]
+      } else {
+        Vec::new( )
+      };
+    SortedOrder { ordering, num_ops, orders: Vec::new( ), history, keys: Vec::with_capacity( num_ops ) }
+  }
+}
+
+impl< P: SearchProblem > ChildOrder< P > for SortedOrder {
+  fn order< H: ProblemHeuristic< P > >( &mut self, problem: &P, h: &H, state: &mut P::State, fsm: &PruningFsm, depth: usize, frame: Frame ) {
+    let (_, fsm_state, hx, fx) = frame;
+    let ops = problem.ops( );
+    self.keys.clear( );
+
+    for (op_idx, &op) in ops.iter( ).enumerate( ) {
+      // Pruned operators go last (where the search skips them again)
+      let key =
+        if fsm.next( fsm_state, op_idx ) == PruningFsm::PRUNED {
+          u32::MAX
+        } else {
+          match self.ordering {
+            MoveOrdering::Heuristic => {
+              let op_cost = problem.cost( state, op );
+              if problem.apply( state, op ) {
+                // The frame's bound may exceed an inconsistent heuristic's
+                // estimate (see `super::solve_ida_problem_bpmx`), and bounds
+                // the child too
+                let next_hx =
+                  if !h.is_consistent( ) {
+                    h.estimate( state ).max( hx.saturating_sub( op_cost ) )
+                  } else {
+                    h.estimate_applied( state, op, hx )
+                  };
+                problem.undo( state, op );
+                ( fx + op_cost ).saturating_add( next_hx )
+              } else {
+                u32::MAX
+              }
+            },
+            MoveOrdering::History => u32::MAX - 1 - self.history[ fsm_state as usize * self.num_ops + op_idx ].min( u32::MAX - 1 ),
+            MoveOrdering::Fixed => op_idx as u32,
+          }
+        };
+      self.keys.push( (key, op_idx as u8) );
+    }
+    // Ties remain in the order of the operators
+    self.keys.sort_by_key( |&(key, _)| key );
+
+    let start = depth * self.num_ops;
+    if self.orders.len( ) < start + self.num_ops {
+      self.orders.resize( start + self.num_ops, 0 );
+    }
+    for (out, &(_, op_idx)) in self.orders[ start..start + self.num_ops ].iter_mut( ).zip( &self.keys ) {
+      *out = op_idx;
+    }
+  }
+
+  #[inline]
+  fn op_idx( &self, depth: usize, i: usize ) -> usize {
+    self.orders[ depth * self.num_ops + i ] as usize
+  }
+
+  #[inline]
+  fn record( &mut self, fsm_state: u16, op_idx: usize, prev_gx: u32, gx: u32 ) {
+    if self.ordering == MoveOrdering::History && gx <= prev_gx {
+      let count = &mut self.history[ fsm_state as usize * self.num_ops + op_idx ];
+      *count = count.saturating_add( 1 );
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{Board, Dir, Goal, TileCosts};
+  use crate::heuristic::{ManhattanDistance, WeightedManhattanDistance};
+  use crate::search::{Budget, PruningFsm, Puzzle, SearchOutcome, WeightedSlidingPuzzle, solve_ida_problem_ordered, solve_ita_ordered};
+  use crate::testing::{all_grids, apply_path, bfs_distances, dijkstra_costs, random_grids};
+  use super::MoveOrdering;
+
+  const ORDERINGS: [MoveOrdering; 3] = [ MoveOrdering::Fixed, MoveOrdering::Heuristic, MoveOrdering::History ];
+
+  #[test]
+  fn orderings_keep_the_optimal_length( ) {
+    let goal = Goal::< 3, 3 >::solved( );
+    let distances = bfs_distances( &goal, &Board::open( ) );
+    let h = ManhattanDistance::< 3, 3 >::new( );
+    let learned = PruningFsm::learn_sliding( 6 );
+
+    for fsm in [ &PruningFsm::SLIDING_INVERSES, &learned ] {
+      // The nodes of the last iterations, by ordering
+      let mut num_last_steps = [0; 3];
+      for p in random_grids::< 3, 3 >( &goal, 30, 60, 4 ) {
+        for (i, ordering) in ORDERINGS.into_iter( ).enumerate( ) {
+          let (outcome, stats) = solve_ita_ordered( &h, p, &goal, fsm, ordering, Budget::default( ) );
+          let SearchOutcome::Solved( path ) = outcome else {
+            panic!( "{:?} is unsolved with {:?}", p, ordering );
+          };
+          assert_eq!( path.len( ) as u32, distances[ &p ], "{:?} with {:?}", p, ordering );
+          assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+          num_last_steps[ i ] += stats.num_last_steps;
+        }
+      }
+      // Sorting by the heuristic finds the solutions sooner
+      assert!( num_last_steps[ 1 ] < num_last_steps[ 0 ], "{:?}", num_last_steps );
+    }
+  }
+
+  #[test]
+  fn weighted_orderings_keep_the_cheapest_cost( ) {
+    let goal = Goal::< 2, 3 >::solved( );
+    let board = Board::open( );
+    let costs = TileCosts::< 2, 3 >::new( &[0, 1, 3, 2, 1, 4] );
+    let problem = WeightedSlidingPuzzle::new( &goal, &board, &costs );
+    let h = WeightedManhattanDistance::new( goal, costs );
+    let expected = dijkstra_costs( &goal, &board, &costs );
+
+    for p in all_grids::< 2, 3 >( ) {
+      let Some( &cost ) = expected.get( &p ) else {
+        continue;
+      };
+      for ordering in ORDERINGS {
+        let SearchOutcome::Solved( ops ) = solve_ida_problem_ordered( &problem, &h, Puzzle::new( p ), &PruningFsm::SLIDING_INVERSES, ordering, Budget::default( ) ).0 else {
+          panic!( "{:?} is unsolved with {:?}", p, ordering );
+        };
+        // The operators move the gap, opposite to the tiles
+        let path: Vec< Dir > = ops.iter( ).map( |dir| dir.inv( ) ).collect( );
+        assert_eq!( costs.path_cost( &p, &path ), Some( cost ), "{:?} with {:?}", p, ordering );
+      }
+    }
+  }
+}
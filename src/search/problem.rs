@@ -0,0 +1,631 @@
+//! A generic search problem, for which [`solve_ida_problem`] runs the same
+//! IDA* as the sliding puzzle solvers. (e.g., for other permutation puzzles,
+//! like TopSpin or pancake sorting)
+//!
+//! A [`SearchProblem`] defines the states, the operators (which are applied and
+//! undone in place, with their costs, and which operator undoes which), and the
+//! goal test. [`solve_ida_problem`] solves it with a [`ProblemHeuristic`]. The
+//! sliding puzzle itself is the [`SlidingPuzzle`] problem, or the
+//! [`WeightedSlidingPuzzle`] with move costs on a board.
+
+// stdlib imports
+use std::marker::PhantomData;
+// local imports
+use crate::grid::{Board, Dir, PuzzleGrid, TileCosts};
+use crate::heuristic::CostHeuristic;
+use super::{Budget, BudgetMeter, DfsResult, GoalTest, PruningFsm, Puzzle, SearchOutcome, SearchStats, SearchStack};
+use super::ordering::{ChildOrder, FixedOrder, MoveOrdering, SortedOrder};
+use super::transposition::{HashedProblem, NoTable, TranspositionTable, Transpositions};
+
+
+/// A search problem, which IDA* solves. (See [`solve_ida_problem`]) Its
+/// operators turn states into other states, at some cost.
+/// 
+/// The search keeps a single state, to which it applies operators in place.
+/// When it backtracks, it undoes them. (So, it never copies states)
+pub trait SearchProblem {
+  type State;
+  type Op: Copy + Eq;
+
+  /// All operators, in the order in which the search tries them. (At most
+  /// 255)
+  fn ops( &self ) -> &[Self::Op];
+
+  /// Applies the operator to the state, in place. Returns `false` (leaving the
+  /// state unchanged) if it does not apply.
+  fn apply( &self, state: &mut Self::State, op: Self::Op ) -> bool;
+
+  /// Undoes the operator, which was last applied to the state.
+  fn undo( &self, state: &mut Self::State, op: Self::Op );
+
+  /// Returns whether `op` undoes `prev_op`. The search never applies it right
+  /// after `prev_op`, as that returns to the previous state. (See
+  /// [`PruningFsm::inverses()`])
+  fn is_inverse( &self, op: Self::Op, prev_op: Self::Op ) -> bool;
+
+  /// Returns the cost of applying the operator to the state, before it is
+  /// applied. (By default, every operator costs 1)
+  #[inline]
+  fn cost( &self, _state: &Self::State, _op: Self::Op ) -> u32 {
+    1
+  }
+
+  fn is_goal( &self, state: &Self::State ) -> bool;
+}
+
+/// A heuristic for the states of a [`SearchProblem`].
+///
+/// Every [`CostHeuristic`] (so, every [`crate::Heuristic`]) is one for the
+/// sliding puzzle problems.
+pub trait ProblemHeuristic< P: SearchProblem > {
+  /// An admissible heuristic. It *underapproximates* the total cost of the
+  /// operators to a goal state.
+  fn estimate( &self, state: &P::State ) -> u32;
+
+  /// Like [`ProblemHeuristic::estimate()`], but for the state reached by
+  /// applying `op` to a state with estimate `prev_hx`. Heuristics which update
+  /// incrementally override this. (By default, it recomputes)
+  #[inline]
+  fn estimate_applied( &self, state: &P::State, _op: P::Op, _prev_hx: u32 ) -> u32 {
+    self.estimate( state )
+  }
+
+  /// Whether no operator decreases the heuristic by more than its cost. If
+  /// not, [`solve_ida_problem_bpmx`] propagates bounds between parents and
+  /// children (i.e., bidirectional pathmax), and only calls
+  /// [`ProblemHeuristic::estimate()`]. (By default, it is consistent)
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    true
+  }
+}
+
+/// IDA* on the search problem. Every iteration takes as bound the smallest
+/// estimated cost that exceeded the previous bound. (Like
+/// [`super::solve_ita_weighted`]) The solution contains the applied operators.
+/// Note that solutions with more than 255 operators are not found.
+pub fn solve_ida_problem< P: SearchProblem, H: ProblemHeuristic< P > >( problem: &P, h: &H, start: P::State, budget: Budget ) -> (SearchOutcome< P::Op >, SearchStats) {
+  solve_ida_problem_pruned( problem, h, start, &PruningFsm::inverses( problem ), budget )
+}
+
+/// Like [`solve_ida_problem`], but prunes the paths which the automaton
+/// rejects, rather than only inverse operators. (e.g., see
+/// [`PruningFsm::learn()`])
+pub fn solve_ida_problem_pruned< P: SearchProblem, H: ProblemHeuristic< P > >( problem: &P, h: &H, start: P::State, fsm: &PruningFsm, budget: Budget ) -> (SearchOutcome< P::Op >, SearchStats) {
+  assert!( fsm.num_ops( ) == problem.ops( ).len( ), "the automaton must be over the problem's operators" );
+  let mut stack = SearchStack::uninit( );
+  let (result, stats) = solve_ida_problem_state( &mut stack, problem, h, start, &mut DfsOptions { fsm, table: &mut NoTable, order: &mut FixedOrder, is_bpmx: false }, budget );
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.ops( ).to_vec( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// Like [`solve_ida_problem_pruned`], but skips the states which the `table`
+/// remembers, and raises their heuristic. (See [`TranspositionTable`]) The
+/// table forgets its earlier states first.
+/// 
+/// This preserves optimality, unless distinct states collide in the table.
+pub fn solve_ida_problem_table< P: HashedProblem, H: ProblemHeuristic< P > >( problem: &P, h: &H, start: P::State, fsm: &PruningFsm, table: &mut TranspositionTable, budget: Budget ) -> (SearchOutcome< P::Op >, SearchStats) {
+  assert!( fsm.num_ops( ) == problem.ops( ).len( ), "the automaton must be over the problem's operators" );
+  let mut stack = SearchStack::uninit( );
+  table.clear( );
+  let (result, stats) = solve_ida_problem_state( &mut stack, problem, h, start, &mut DfsOptions { fsm, table, order: &mut FixedOrder, is_bpmx: false }, budget );
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.ops( ).to_vec( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// Like [`solve_ida_problem_pruned`], but tries the children of every state in
+/// the given order. (See [`MoveOrdering`])
+pub fn solve_ida_problem_ordered< P: SearchProblem, H: ProblemHeuristic< P > >( problem: &P, h: &H, start: P::State, fsm: &PruningFsm, ordering: MoveOrdering, budget: Budget ) -> (SearchOutcome< P::Op >, SearchStats) {
+  assert!( fsm.num_ops( ) == problem.ops( ).len( ), "the automaton must be over the problem's operators" );
+  let mut stack = SearchStack::uninit( );
+  let (result, stats) =
+    if ordering == MoveOrdering::Fixed {
+      solve_ida_problem_state( &mut stack, problem, h, start, &mut DfsOptions { fsm, table: &mut NoTable, order: &mut FixedOrder, is_bpmx: false }, budget )
+    } else {
+      let mut order = SortedOrder::new( ordering, problem.ops( ).len( ), fsm );
+      solve_ida_problem_state( &mut stack, problem, h, start, &mut DfsOptions { fsm, table: &mut NoTable, order: &mut order, is_bpmx: false }, budget )
+    };
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.ops( ).to_vec( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// Like [`solve_ida_problem_pruned`], but propagates the bounds of an
+/// inconsistent heuristic between parents and children. (i.e., bidirectional
+/// pathmax, or BPMX; see [`ProblemHeuristic::is_consistent()`]) A parent's
+/// bound less the operator's cost bounds its children, and a child's bound less
+/// the cost bounds its parent, which backtracks as soon as that exceeds the
+/// IDA* bound. For a consistent heuristic, this is [`solve_ida_problem_pruned`].
+pub fn solve_ida_problem_bpmx< P: SearchProblem, H: ProblemHeuristic< P > >( problem: &P, h: &H, start: P::State, fsm: &PruningFsm, budget: Budget ) -> (SearchOutcome< P::Op >, SearchStats) {
+  assert!( fsm.num_ops( ) == problem.ops( ).len( ), "the automaton must be over the problem's operators" );
+  let mut stack = SearchStack::uninit( );
+  let (result, stats) = solve_ida_problem_state( &mut stack, problem, h, start, &mut DfsOptions { fsm, table: &mut NoTable, order: &mut FixedOrder, is_bpmx: true }, budget );
+
+  let outcome =
+    match result {
+      DfsResult::Solved => SearchOutcome::Solved( stack.ops( ).to_vec( ) ),
+      DfsResult::Exhausted => SearchOutcome::Exhausted,
+      DfsResult::OutOfBudget => SearchOutcome::OutOfBudget,
+    };
+  (outcome, stats)
+}
+
+/// The enhancements of a depth-first search on a [`SearchProblem`], besides
+/// its heuristic.
+pub(super) struct DfsOptions< 'a, T, O > {
+  /// Prunes the paths it rejects
+  pub fsm: &'a PruningFsm,
+  /// Skips the states it remembers
+  pub table: &'a mut T,
+  /// Orders the children of every state
+  pub order: &'a mut O,
+  /// Whether to apply bidirectional pathmax, if the heuristic is inconsistent.
+  /// (See [`solve_ida_problem_bpmx`])
+  pub is_bpmx: bool,
+}
+
+/// Like [`solve_ida_problem`], but leaves the solution on the `stack`.
+pub(super) fn solve_ida_problem_state< P: SearchProblem, H: ProblemHeuristic< P >, T: Transpositions< P >, O: ChildOrder< P > >( stack: &mut SearchStack< P::State, P::Op >, problem: &P, h: &H, start: P::State, opts: &mut DfsOptions< T, O >, budget: Budget ) -> (DfsResult, SearchStats) {
+  let mut meter = BudgetMeter::new( budget );
+  let mut stats = SearchStats::default( );
+  let mut prev_num_steps = 0;
+  let is_solved = problem.is_goal( &start );
+  let mut max_cost = h.estimate( &start );
+  stack.init( start );
+
+  let result =
+    if is_solved { // it's solved already
+      DfsResult::Solved
+    } else {
+      loop {
+        // An exhausted search undoes all its operators, so every iteration
+        // starts from the initial state.
+        prev_num_steps = meter.num_steps;
+        let (result, next_max_cost) = solve_dfs_problem( stack, problem, h, opts, max_cost, &mut meter );
+        stats.num_iterations += 1;
+
+        if result != DfsResult::Exhausted {
+          break result;
+        }
+
+        match next_max_cost {
+          Some( c ) => max_cost = c,
+          None => break DfsResult::Exhausted, // nothing was cut off
+        }
+      }
+    };
+
+  stats.num_steps = meter.num_steps;
+  stats.num_last_steps = meter.num_steps - prev_num_steps;
+  stats.elapsed = meter.elapsed( );
+  (result, stats)
+}
+
+/// A depth-first search bounded by `max_cost`, from the state of the `stack`.
+/// (See [`SearchStack::init()`]) It skips the operators which the automaton
+/// prunes, and the states which the table skips. It tries the operators in the
+/// options' order. Also returns the smallest estimated cost which exceeded
+/// `max_cost`, if any.
+/// 
+/// If the search is exhausted, the stack's state is the initial state again.
+/// If it is solved, it's the goal state.
+#[inline]
+pub(super) fn solve_dfs_problem< P: SearchProblem, H: ProblemHeuristic< P >, T: Transpositions< P >, O: ChildOrder< P > >( stack: &mut SearchStack< P::State, P::Op >, problem: &P, h: &H, opts: &mut DfsOptions< T, O >, max_cost: u32, meter: &mut BudgetMeter ) -> (DfsResult, Option< u32 >) {
+  let DfsOptions { fsm, ref mut table, ref mut order, is_bpmx } = *opts;
+  let mut num_steps = 0;
+  let mut next_max_cost: Option< u32 > = None;
+  let ops = problem.ops( );
+  // Bidirectional pathmax, for inconsistent heuristics
+  let is_bpmx = is_bpmx && !h.is_consistent( );
+
+  let mut state = stack.state.take( ).expect( "the stack is initialised" );
+  let hx = h.estimate( &state );
+  stack.init_frames( hx );
+  table.begin( problem, &state, hx );
+  order.order( problem, h, &mut state, fsm, 0, (0, 0, hx, 0) );
+
+  let result =
+    loop {
+      let Some( (opt_top_op, (ref mut next_op_idx, fsm_state, hx, fx)) ) = stack.top_mut( ) else {
+        break DfsResult::Exhausted;
+      };
+      let (i, fsm_state, hx, fx) = (*next_op_idx as usize, *fsm_state, *hx, *fx);
+
+      if i == ops.len( ) {
+        // Backtrack to the previous state
+        if let Some( top_op ) = opt_top_op {
+          problem.undo( &mut state, top_op );
+        }
+        unsafe { stack.pop( ); }
+        table.pop( );
+
+        // The bound of the state (less its cost) also bounds its parent. If
+        // that exceeds `max_cost`, the parent backtracks too.
+        if is_bpmx {
+          if let Some( (_, (parent_next_op_idx, _, parent_hx, parent_fx)) ) = stack.top_mut( ) {
+            let op_cost = fx - *parent_fx;
+
+            if hx > *parent_hx + op_cost {
+              *parent_hx = hx - op_cost;
+              let parent_gx = *parent_fx + *parent_hx;
+
+              if parent_gx > max_cost {
+                *parent_next_op_idx = ops.len( ) as u8;
+                next_max_cost = Some( next_max_cost.map_or( parent_gx, |c| c.min( parent_gx ) ) );
+                table.cut( parent_gx );
+              }
+            }
+          }
+        }
+        continue;
+      }
+      *next_op_idx += 1;
+      let depth = stack.ops( ).len( );
+      let op_idx = order.op_idx( depth, i );
+      let op = ops[ op_idx ];
+
+      // No need to reach a state which a smaller path reaches. (e.g., to go
+      // back to the state from which we just came)
+      let next_fsm_state = fsm.next( fsm_state, op_idx );
+      if next_fsm_state == PruningFsm::PRUNED {
+        continue;
+      }
+
+      let op_cost = problem.cost( &state, op );
+
+      if problem.apply( &mut state, op ) {
+        num_steps += 1;
+
+        if !meter.allows( num_steps ) {
+          break DfsResult::OutOfBudget;
+        }
+
+        let next_fx = fx + op_cost; // the path cost
+        let next_hx = // a lower-bound on our remaining cost
+          if is_bpmx {
+            // The state's bound (less the cost) also bounds the child
+            h.estimate( &state ).max( hx.saturating_sub( op_cost ) )
+          } else {
+            h.estimate_applied( &state, op, hx )
+          };
+
+        // The child's bound (less the cost) also bounds the state. If that
+        // exceeds `max_cost`, the state backtracks, without its other children.
+        if is_bpmx && next_hx > hx + op_cost {
+          let (_, (next_op_idx, _, hx, _)) = stack.top_mut( ).expect( "the state is on the stack" );
+          *hx = next_hx - op_cost;
+          let gx = fx + *hx;
+          order.record( fsm_state, op_idx, gx, next_fx + next_hx );
+
+          if gx > max_cost {
+            *next_op_idx = ops.len( ) as u8;
+            next_max_cost = Some( next_max_cost.map_or( gx, |c| c.min( gx ) ) );
+            table.cut( gx );
+            problem.undo( &mut state, op );
+            continue;
+          }
+        } else {
+          order.record( fsm_state, op_idx, fx + hx, next_fx + next_hx );
+        }
+
+        // The table skips states which were searched already, and may raise
+        // their bound. (But the frame keeps the heuristic's own estimate, which
+        // its children update)
+        let Some( bound_hx ) = table.visit( problem, &state, next_fsm_state, next_fx, next_hx ) else {
+          problem.undo( &mut state, op );
+          continue;
+        };
+        let gx = next_fx.saturating_add( bound_hx ); // a lower-bound on our total path cost
+
+        if gx > max_cost {
+          next_max_cost = Some( next_max_cost.map_or( gx, |c| c.min( gx ) ) );
+          table.cut( gx );
+          problem.undo( &mut state, op );
+        } else if stack.ops( ).len( ) < 255 { // the stack's capacity (see `SearchStack::uninit`)
+          unsafe { stack.push_unchecked( op, next_fsm_state, next_hx, next_fx ); }
+          table.push( );
+
+          // An admissible heuristic is 0 at the goal, so we only test for the
+          // goal then.
+          if next_hx == 0 && problem.is_goal( &state ) { // we're at the solution
+            break DfsResult::Solved;
+          }
+          order.order( problem, h, &mut state, fsm, depth + 1, (0, next_fsm_state, next_hx, next_fx) );
+        } else {
+          table.cut( gx );
+          problem.undo( &mut state, op );
+        }
+      }
+    };
+
+  stack.state = Some( state );
+  meter.num_steps += num_steps;
+  (result, next_max_cost)
+}
+
+/// The sliding puzzle with unit moves, solved to the `goal`. (See
+/// [`GoalTest`]) Its operators are the moves of the gap. (i.e., the "inverted"
+/// directions of the moved tiles)
+pub struct SlidingPuzzle< 'a, G, T, const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: &'a T,
+  grid: PhantomData< G >,
+}
+
+impl< 'a, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > SlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > {
+  #[inline]
+  pub fn new( goal: &'a T ) -> Self {
+    SlidingPuzzle { goal, grid: PhantomData }
+  }
+}
+
+impl< 'a, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > SearchProblem for SlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > {
+  type State = Puzzle< G, WIDTH, HEIGHT >;
+  type Op = Dir;
+
+  #[inline]
+  fn ops( &self ) -> &[Dir] {
+    &Dir::ALL
+  }
+
+  #[inline]
+  fn apply( &self, p: &mut Self::State, inv_dir: Dir ) -> bool {
+    p.step_inv_mut( inv_dir )
+  }
+
+  #[inline]
+  fn undo( &self, p: &mut Self::State, inv_dir: Dir ) {
+    let is_undone = p.step_inv_mut( inv_dir.inv( ) );
+    debug_assert!( is_undone );
+  }
+
+  #[inline]
+  fn is_inverse( &self, inv_dir: Dir, prev_inv_dir: Dir ) -> bool {
+    inv_dir == prev_inv_dir.inv( )
+  }
+
+  #[inline]
+  fn is_goal( &self, p: &Self::State ) -> bool {
+    self.goal.is_goal( &p.grid )
+  }
+}
+
+impl< 'a, C: CostHeuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > ProblemHeuristic< SlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > > for C {
+  #[inline]
+  fn estimate( &self, p: &Puzzle< G, WIDTH, HEIGHT > ) -> u32 {
+    self.compute_cost( &p.grid )
+  }
+
+  #[inline]
+  fn estimate_applied( &self, p: &Puzzle< G, WIDTH, HEIGHT >, inv_dir: Dir, prev_hx: u32 ) -> u32 {
+    // The moved tile left the gap's cell, for the gap's previous cell
+    match p.gap_cell.step( inv_dir.inv( ) ) {
+      Some( prev_gap_cell ) => {
+        let tile = p.grid.get_tile( prev_gap_cell );
+        self.compute_cost_moved( &p.grid, prev_hx, tile, p.gap_cell, prev_gap_cell )
+      },
+      None => self.compute_cost( &p.grid ),
+    }
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    CostHeuristic::is_consistent( self )
+  }
+}
+
+/// Like [`SlidingPuzzle`], but tiles have move costs, and the gap moves on the
+/// `board`. (See [`super::solve_ita_board`])
+pub struct WeightedSlidingPuzzle< 'a, G, T, const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  goal: &'a T,
+  board: &'a Board< WIDTH, HEIGHT >,
+  costs: &'a TileCosts< WIDTH, HEIGHT >,
+  grid: PhantomData< G >,
+}
+
+impl< 'a, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > WeightedSlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > {
+  #[inline]
+  pub fn new( goal: &'a T, board: &'a Board< WIDTH, HEIGHT >, costs: &'a TileCosts< WIDTH, HEIGHT > ) -> Self {
+    WeightedSlidingPuzzle { goal, board, costs, grid: PhantomData }
+  }
+}
+
+impl< 'a, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > SearchProblem for WeightedSlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > {
+  type State = Puzzle< G, WIDTH, HEIGHT >;
+  type Op = Dir;
+
+  #[inline]
+  fn ops( &self ) -> &[Dir] {
+    &Dir::ALL
+  }
+
+  #[inline]
+  fn apply( &self, p: &mut Self::State, inv_dir: Dir ) -> bool {
+    p.step_inv_on_mut( inv_dir, self.board )
+  }
+
+  #[inline]
+  fn undo( &self, p: &mut Self::State, inv_dir: Dir ) {
+    let is_undone = p.step_inv_on_mut( inv_dir.inv( ), self.board );
+    debug_assert!( is_undone );
+  }
+
+  #[inline]
+  fn is_inverse( &self, inv_dir: Dir, prev_inv_dir: Dir ) -> bool {
+    inv_dir == prev_inv_dir.inv( )
+  }
+
+  /// The cost of the tile which moves into the gap.
+  #[inline]
+  fn cost( &self, p: &Self::State, inv_dir: Dir ) -> u32 {
+    match self.board.step( p.gap_cell, inv_dir ) {
+      Some( adj_cell ) => self.costs.cost( p.grid.get_tile( adj_cell ) ) as u32,
+      None => 0,
+    }
+  }
+
+  #[inline]
+  fn is_goal( &self, p: &Self::State ) -> bool {
+    self.goal.is_goal( &p.grid )
+  }
+}
+
+impl< 'a, C: CostHeuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > ProblemHeuristic< WeightedSlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > > for C {
+  #[inline]
+  fn estimate( &self, p: &Puzzle< G, WIDTH, HEIGHT > ) -> u32 {
+    self.compute_cost( &p.grid )
+  }
+
+  #[inline]
+  fn is_consistent( &self ) -> bool {
+    CostHeuristic::is_consistent( self )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  // stdlib imports
+  use std::cmp::Reverse;
+  use std::collections::{BinaryHeap, HashMap};
+  // local imports
+  use crate::testing::permutations;
+  use super::*;
+
+  /// Pancake sorting, where a flip reverses the `n` topmost pancakes. (At
+  /// cost `n`, if weighted)
+  struct Pancakes {
+    ops: Vec< u8 >,
+    is_weighted: bool,
+  }
+
+  impl Pancakes {
+    fn new( num_pancakes: u8, is_weighted: bool ) -> Self {
+      Pancakes { ops: ( 2..=num_pancakes ).collect( ), is_weighted }
+    }
+  }
+
+  impl SearchProblem for Pancakes {
+    type State = Vec< u8 >;
+    type Op = u8;
+
+    fn ops( &self ) -> &[u8] {
+      &self.ops
+    }
+
+    fn apply( &self, state: &mut Vec< u8 >, n: u8 ) -> bool {
+      state[ ..n as usize ].reverse( );
+      true
+    }
+
+    fn undo( &self, state: &mut Vec< u8 >, n: u8 ) {
+      state[ ..n as usize ].reverse( );
+    }
+
+    fn is_inverse( &self, n: u8, prev_n: u8 ) -> bool {
+      n == prev_n
+    }
+
+    fn cost( &self, _state: &Vec< u8 >, n: u8 ) -> u32 {
+      if self.is_weighted { n as u32 } else { 1 }
+    }
+
+    fn is_goal( &self, state: &Vec< u8 > ) -> bool {
+      state.is_sorted( )
+    }
+  }
+
+  /// The gap heuristic, which counts the adjacent pancakes (including the
+  /// plate, below) of non-consecutive sizes. A flip changes only the gap at
+  /// its bottom, and costs at least 2 if weighted.
+  struct Gaps;
+
+  impl ProblemHeuristic< Pancakes > for Gaps {
+    fn estimate( &self, state: &Vec< u8 > ) -> u32 {
+      let plate = state.len( ) as u8;
+      state.iter( ).zip( state[ 1.. ].iter( ).chain( [&plate] ) ).filter( |(&a, &b)| a.abs_diff( b ) != 1 ).count( ) as u32
+    }
+  }
+
+  /// Scales the gap heuristic to the weighted flips.
+  struct WeightedGaps;
+
+  impl ProblemHeuristic< Pancakes > for WeightedGaps {
+    fn estimate( &self, state: &Vec< u8 > ) -> u32 {
+      2 * Gaps.estimate( state )
+    }
+  }
+
+  /// The cheapest costs to sort the stacks. (Flips are their own inverses, at
+  /// the same cost)
+  fn pancake_costs( problem: &Pancakes, num_pancakes: u8 ) -> HashMap< Vec< u8 >, u32 > {
+    let mut costs = HashMap::new( );
+    let mut heap = BinaryHeap::new( );
+    heap.push( Reverse( (0, ( 0..num_pancakes ).collect::< Vec< u8 > >( )) ) );
+
+    while let Some( Reverse( (cost, state) ) ) = heap.pop( ) {
+      if costs.contains_key( &state ) {
+        continue;
+      }
+      for &n in problem.ops( ) {
+        let mut next = state.clone( );
+        problem.apply( &mut next, n );
+        if !costs.contains_key( &next ) {
+          heap.push( Reverse( (cost + problem.cost( &state, n ), next) ) );
+        }
+      }
+      costs.insert( state, cost );
+    }
+    costs
+  }
+
+  fn check_pancakes< H: ProblemHeuristic< Pancakes > >( problem: &Pancakes, h: &H, num_pancakes: u8 ) {
+    let costs = pancake_costs( problem, num_pancakes );
+    assert_eq!( costs.len( ), permutations( num_pancakes as usize ).len( ) );
+    let fsm = PruningFsm::learn( problem, &( 0..num_pancakes ).collect( ), 4 );
+
+    for (start, &cost) in &costs {
+      assert!( h.estimate( start ) <= cost, "{:?}", start );
+
+      let mut outcomes = vec![ solve_ida_problem( problem, h, start.clone( ), Budget::default( ) ).0 ];
+      // Duplicates are only shorter, not cheaper
+      if !problem.is_weighted {
+        outcomes.push( solve_ida_problem_pruned( problem, h, start.clone( ), &fsm, Budget::default( ) ).0 );
+      }
+      for outcome in outcomes {
+        let SearchOutcome::Solved( path ) = outcome else {
+          panic!( "{:?} is unsolved", start );
+        };
+        let mut state = start.clone( );
+        let mut path_cost = 0;
+        for &n in &path {
+          path_cost += problem.cost( &state, n );
+          problem.apply( &mut state, n );
+        }
+        assert!( problem.is_goal( &state ), "{:?}", start );
+        assert_eq!( path_cost, cost, "{:?}", start );
+      }
+    }
+  }
+
+  #[test]
+  fn pancakes_are_sorted_optimally( ) {
+    check_pancakes( &Pancakes::new( 6, false ), &Gaps, 6 );
+    check_pancakes( &Pancakes::new( 6, true ), &WeightedGaps, 6 );
+  }
+}
@@ -0,0 +1,374 @@
+//! Duplicate-move pruning with a finite-state machine, which generalises
+//! inverse-move elimination. (See *Finding Duplicate Nodes in Search Spaces*
+//! (1993), by Larry A. Taylor and Richard E. Korf)
+//!
+//! A *duplicate* is a string of operators which reaches the same state as a
+//! shorter (or equally long, but lexicographically smaller) string. No optimal
+//! path needs to contain a duplicate, as it can be replaced by the smaller
+//! string. The automaton recognises all duplicates at once, while the search
+//! applies operators.
+//!
+//! [`PruningFsm::learn_sliding()`] discovers the duplicates of the sliding
+//! puzzle (e.g., up to 12 moves), for [`crate::solve_ita_pruned()`], and
+//! [`PruningFsm::learn()`] does so for other problems, for
+//! [`crate::solve_ida_problem_pruned()`]. The automaton is a table of
+//! transitions, which [`PruningFsm::from_table()`] embeds as a constant.
+
+// stdlib imports
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+// local imports
+use crate::grid::Dir;
+use super::SearchProblem;
+
+
+/// A finite-state machine over the operators of a [`SearchProblem`], which
+/// rejects paths ending in a duplicate. Operators are identified by their
+/// index in [`SearchProblem::ops()`]. The search tracks its state at every
+/// depth. (See [`super::solve_ida_problem_pruned`])
+///
+/// The transitions form a table, which can be precomputed and embedded as a
+/// constant. (See [`PruningFsm::table()`] and [`PruningFsm::from_table()`])
+///
+/// Pruning duplicates preserves optimality when every operator costs 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PruningFsm {
+  num_ops: usize,
+  /// The next state, indexed by `state * num_ops + op_idx`. (Or
+  /// [`PruningFsm::PRUNED`]) State 0 is the initial state.
+  table: Cow< 'static, [u16] >,
+}
+
+impl PruningFsm {
+  /// The transition into a duplicate.
+  pub const PRUNED: u16 = u16::MAX;
+
+  /// Only prunes the inverse of the previous move, for the sliding puzzle.
+  /// (i.e., [`super::SlidingPuzzle`], whose operators are [`Dir::ALL`]) Every
+  /// state, except the initial one, is the previous move of the gap.
+  pub const SLIDING_INVERSES: PruningFsm =
+    PruningFsm::from_table( 4, &
+      [ 1, 2, 3, 4 // initial
+      , 1, 2, PruningFsm::PRUNED, 4 // after up
+      , 1, 2, 3, PruningFsm::PRUNED // after right
+      , PruningFsm::PRUNED, 2, 3, 4 // after down
+      , 1, PruningFsm::PRUNED, 3, 4 // after left
+      ]
+    );
+
+  /// Takes a table, as returned by [`PruningFsm::table()`]. Its length must
+  /// be a multiple of `num_ops`, and it must only contain states in range.
+  pub const fn from_table( num_ops: usize, table: &'static [u16] ) -> Self {
+    assert!( num_ops > 0 && table.len( ).is_multiple_of( num_ops ), "the table must contain a row for every state" );
+    let mut i = 0;
+    while i < table.len( ) {
+      assert!( table[ i ] == PruningFsm::PRUNED || ( table[ i ] as usize ) < table.len( ) / num_ops, "the table contains a state out of range" );
+      i += 1;
+    }
+    PruningFsm { num_ops, table: Cow::Borrowed( table ) }
+  }
+
+  /// Only prunes the inverse of the previous operator. (See
+  /// [`SearchProblem::is_inverse()`])
+  pub fn inverses< P: SearchProblem >( problem: &P ) -> Self {
+    let ops = problem.ops( );
+    let num_ops = ops.len( );
+    let mut table = Vec::with_capacity( ( num_ops + 1 ) * num_ops );
+
+    for prev_op_idx in 0..=num_ops {
+      for (op_idx, &op) in ops.iter( ).enumerate( ) {
+        if prev_op_idx > 0 && problem.is_inverse( op, ops[ prev_op_idx - 1 ] ) {
+          table.push( PruningFsm::PRUNED );
+        } else {
+          table.push( op_idx as u16 + 1 );
+        }
+      }
+    }
+    PruningFsm { num_ops, table: Cow::Owned( table ) }
+  }
+
+  /// Compiles the duplicates into an automaton, which rejects every path with
+  /// a duplicate as a suffix. (i.e., the Aho-Corasick automaton) Duplicates
+  /// contain operator indices, below `num_ops`.
+  pub fn from_duplicates( num_ops: usize, duplicates: &[Vec< u8 >] ) -> Self {
+    assert!( num_ops > 0, "there must be an operator" );
+
+    // The trie of the duplicates
+    let mut children: Vec< Vec< Option< usize > > > = vec![ vec![ None; num_ops ] ];
+    let mut is_pruned = vec![ false ];
+
+    for duplicate in duplicates {
+      let mut node = 0;
+      for &op_idx in duplicate {
+        assert!( ( op_idx as usize ) < num_ops, "the duplicate contains an operator out of range" );
+        node =
+          match children[ node ][ op_idx as usize ] {
+            Some( child ) => child,
+            None => {
+              children.push( vec![ None; num_ops ] );
+              is_pruned.push( false );
+              children[ node ][ op_idx as usize ] = Some( children.len( ) - 1 );
+              children.len( ) - 1
+            },
+          };
+      }
+      is_pruned[ node ] = true;
+    }
+
+    // The transitions, through the failure links, in breadth-first order
+    let mut transitions = vec![ vec![ 0; num_ops ]; children.len( ) ];
+    let mut fail = vec![ 0; children.len( ) ];
+    let mut order = Vec::with_capacity( children.len( ) );
+    let mut queue = VecDeque::from( [0] );
+
+    while let Some( node ) = queue.pop_front( ) {
+      order.push( node );
+      // A suffix of this path is a duplicate
+      is_pruned[ node ] |= is_pruned[ fail[ node ] ];
+
+      for op_idx in 0..num_ops {
+        match children[ node ][ op_idx ] {
+          Some( child ) => {
+            fail[ child ] = if node == 0 { 0 } else { transitions[ fail[ node ] ][ op_idx ] };
+            transitions[ node ][ op_idx ] = child;
+            queue.push_back( child );
+          },
+          None => {
+            transitions[ node ][ op_idx ] = if node == 0 { 0 } else { transitions[ fail[ node ] ][ op_idx ] };
+          },
+        }
+      }
+    }
+
+    // Number the remaining states, in breadth-first order
+    let mut states = vec![ PruningFsm::PRUNED; children.len( ) ];
+    let mut num_states = 0;
+    for &node in &order {
+      if !is_pruned[ node ] {
+        assert!( num_states < PruningFsm::PRUNED as usize, "the automaton has too many states" );
+        states[ node ] = num_states as u16;
+        num_states += 1;
+      }
+    }
+
+    let mut table = Vec::with_capacity( num_states * num_ops );
+    for &node in &order {
+      if !is_pruned[ node ] {
+        table.extend( transitions[ node ].iter( ).map( |&next| states[ next ] ) );
+      }
+    }
+    PruningFsm { num_ops, table: Cow::Owned( table ) }
+  }
+
+  /// Discovers the duplicates up to `max_len` operators from `start`, and
+  /// compiles them. (See [`PruningFsm::from_duplicates()`])
+  ///
+  /// This is only sound if every operator applies to every state, and the
+  /// effect of operator strings does not depend on the state. (e.g., for
+  /// TopSpin or pancake sorting, where operators permute positions) For the
+  /// sliding puzzle, see [`PruningFsm::learn_sliding()`].
+  pub fn learn< P: SearchProblem >( problem: &P, start: &P::State, max_len: usize ) -> Self where P::State: Clone + Hash + Eq {
+    let ops = problem.ops( );
+    let mut seen: HashSet< P::State > = HashSet::from( [ start.clone( ) ] );
+
+    PruningFsm::learn_with( ops.len( ), max_len, |path| {
+      let mut state = start.clone( );
+      for &op_idx in path {
+        if !problem.apply( &mut state, ops[ op_idx as usize ] ) {
+          return false;
+        }
+      }
+      !seen.insert( state )
+    } )
+  }
+
+  /// Discovers the duplicates of the sliding puzzle up to `max_len` moves,
+  /// and compiles them, for [`super::SlidingPuzzle`].
+  ///
+  /// Moves are explored from a gap far from any edge. A string of moves is
+  /// only a duplicate if the smaller string moves the gap through a subset of
+  /// its cells. So, the smaller string applies wherever the duplicate does,
+  /// on any flat [`crate::Board`], regardless of its size or blocked cells.
+  /// (But not on a torus, where strings may wrap around)
+  ///
+  /// The number of explored strings grows exponentially with `max_len`. (Up
+  /// to 12 moves, this takes seconds)
+  pub fn learn_sliding( max_len: usize ) -> Self {
+    let size = 2 * max_len + 1;
+    let center = max_len * size + max_len;
+    // The smaller strings to every arrangement of the moved tiles (with the
+    // gap's cell), with the cells through which their gaps moved
+    let mut seen: HashMap< Vec< (u16, u16) >, Vec< Vec< u16 > > > = HashMap::from( [ (vec![ (center as u16, center as u16) ], vec![ vec![ center as u16 ] ]) ] );
+    let mut grid: Vec< u16 > = ( 0..( size * size ) as u16 ).collect( );
+
+    PruningFsm::learn_with( Dir::ALL.len( ), max_len, |path| {
+      let mut gap_cell = center;
+      let mut cells = vec![ center as u16 ];
+
+      for &op_idx in path {
+        let next_gap_cell =
+          match Dir::ALL[ op_idx as usize ] {
+            Dir::UP    => gap_cell - size,
+            Dir::RIGHT => gap_cell + 1,
+            Dir::DOWN  => gap_cell + size,
+            Dir::LEFT  => gap_cell - 1,
+          };
+        grid.swap( gap_cell, next_gap_cell );
+        gap_cell = next_gap_cell;
+        cells.push( gap_cell as u16 );
+      }
+
+      cells.sort_unstable( );
+      cells.dedup( );
+      let mut key: Vec< (u16, u16) > = cells.iter( ).filter( |&&c| grid[ c as usize ] != c ).map( |&c| (c, grid[ c as usize ]) ).collect( );
+      key.push( (gap_cell as u16, center as u16) );
+
+      // Restore the grid, for the next string
+      for &c in &cells {
+        grid[ c as usize ] = c;
+      }
+
+      let smaller_cells = seen.entry( key ).or_default( );
+      if smaller_cells.iter( ).any( |s| s.iter( ).all( |c| cells.binary_search( c ).is_ok( ) ) ) {
+        true
+      } else {
+        smaller_cells.push( cells );
+        false
+      }
+    } )
+  }
+
+  /// Enumerates the strings of every length up to `max_len`, in shortlex
+  /// order, except those containing a known duplicate. `is_duplicate` takes
+  /// every string, after all smaller strings.
+  fn learn_with( num_ops: usize, max_len: usize, mut is_duplicate: impl FnMut( &[u8] ) -> bool ) -> Self {
+    let mut duplicates = Vec::new( );
+    let mut fsm = PruningFsm::from_duplicates( num_ops, &duplicates );
+
+    for len in 1..=max_len {
+      let mut path: Vec< u8 > = Vec::with_capacity( len );
+      // The automaton's state before every operator in `path`
+      let mut fsm_states: Vec< u16 > = vec![ 0 ];
+      let mut next_op_idx = 0;
+      let num_duplicates = duplicates.len( );
+
+      loop {
+        if next_op_idx == num_ops {
+          match path.pop( ) {
+            Some( op_idx ) => { fsm_states.pop( ); next_op_idx = op_idx as usize + 1; continue; },
+            None => break,
+          }
+        }
+
+        let fsm_state = fsm.next( fsm_states[ fsm_states.len( ) - 1 ], next_op_idx );
+        if fsm_state != PruningFsm::PRUNED {
+          path.push( next_op_idx as u8 );
+
+          if path.len( ) < len {
+            fsm_states.push( fsm_state );
+            next_op_idx = 0;
+            continue;
+          }
+
+          if is_duplicate( &path ) {
+            duplicates.push( path.clone( ) );
+          }
+          path.pop( );
+        }
+        next_op_idx += 1;
+      }
+
+      if duplicates.len( ) > num_duplicates {
+        fsm = PruningFsm::from_duplicates( num_ops, &duplicates );
+      }
+    }
+    fsm
+  }
+
+  #[inline]
+  pub fn num_ops( &self ) -> usize {
+    self.num_ops
+  }
+
+  #[inline]
+  pub fn num_states( &self ) -> usize {
+    self.table.len( ) / self.num_ops
+  }
+
+  /// Returns the transition table, indexed by `state * num_ops + op_idx`. (To
+  /// embed it, print it with `{:?}`, and pass it to
+  /// [`PruningFsm::from_table()`])
+  #[inline]
+  pub fn table( &self ) -> &[u16] {
+    &self.table
+  }
+
+  /// Returns the state after the operator, or [`PruningFsm::PRUNED`] if the
+  /// path now ends in a duplicate.
+  #[inline]
+  pub fn next( &self, state: u16, op_idx: usize ) -> u16 {
+    debug_assert!( op_idx < self.num_ops );
+    self.table[ state as usize * self.num_ops + op_idx ]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{Board, Goal};
+  use crate::heuristic::ManhattanDistance;
+  use crate::search::{Budget, SearchOutcome, solve_ita_pruned};
+  use crate::testing::{apply_path, bfs_distances, random_grids};
+  use crate::verify::SplitMix64;
+  use super::PruningFsm;
+
+  /// Returns whether the automaton rejects the path.
+  fn is_pruned( fsm: &PruningFsm, path: &[u8] ) -> bool {
+    let mut state = 0;
+    for &op_idx in path {
+      state = fsm.next( state, op_idx as usize );
+      if state == PruningFsm::PRUNED {
+        return true;
+      }
+    }
+    false
+  }
+
+  #[test]
+  fn duplicates_are_pruned_anywhere_in_the_path( ) {
+    let mut rng = SplitMix64( 11 );
+    for _ in 0..20 {
+      let duplicates: Vec< Vec< u8 > > =
+        ( 0..5 ).map( |_| ( 0..1 + rng.next( ) % 4 ).map( |_| ( rng.next( ) % 3 ) as u8 ).collect( ) ).collect( );
+      let fsm = PruningFsm::from_duplicates( 3, &duplicates );
+      assert_eq!( PruningFsm::from_table( 3, fsm.table( ).to_vec( ).leak( ) ), fsm );
+
+      // Every path of up to 6 operators
+      for len in 0..=6 {
+        for i in 0..3usize.pow( len ) {
+          let path: Vec< u8 > = ( 0..len ).map( |j| ( i / 3usize.pow( j ) % 3 ) as u8 ).collect( );
+          let has_duplicate = duplicates.iter( ).any( |d| path.windows( d.len( ) ).any( |w| w == d.as_slice( ) ) );
+          assert_eq!( is_pruned( &fsm, &path ), has_duplicate, "{:?} with {:?}", path, duplicates );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn learned_sliding_pruning_keeps_the_optimal_length( ) {
+    let fsm = PruningFsm::learn_sliding( 8 );
+    // It prunes more than the inverses
+    assert!( fsm.num_states( ) > PruningFsm::SLIDING_INVERSES.num_states( ) );
+
+    let goal = Goal::< 3, 3 >::solved( );
+    let distances = bfs_distances( &goal, &Board::open( ) );
+    let h = ManhattanDistance::< 3, 3 >::new( );
+    for p in random_grids::< 3, 3 >( &goal, 40, 60, 5 ) {
+      let SearchOutcome::Solved( path ) = solve_ita_pruned( &h, p, &goal, &fsm, Budget::default( ) ).0 else {
+        panic!( "{:?} is unsolved", p );
+      };
+      assert_eq!( path.len( ) as u32, distances[ &p ], "{:?}", p );
+      assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+    }
+  }
+}
@@ -0,0 +1,226 @@
+//! IDA* for the multi-tile metric, where sliding several tiles in one row or
+//! column counts as a single move. (i.e., the gap jumps up to
+//! `max(WIDTH, HEIGHT) - 1` cells in one direction)
+//!
+//! [`solve_ita_slides()`] minimises the number of slides, with a
+//! [`SlideHeuristic`] such as [`crate::MaxDisplacement`], or
+//! [`crate::SlideBound`] around any regular heuristic. Its solution contains
+//! single-tile moves, which [`group_slides()`] groups into slides. (e.g., `R3`
+//! moves three tiles to the right)
+
+// stdlib imports
+use std::fmt;
+// local imports
+use crate::grid::{Dir, Goal, PuzzleGrid};
+use crate::heuristic::SlideHeuristic;
+use super::{Budget, BudgetMeter, DfsResult, Puzzle, SearchOutcome, SearchStats};
+
+
+/// A move in the multi-tile metric. `len` tiles move one cell in `dir`. (So
+/// the gap moves `len` cells in the opposite direction)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slide {
+  pub dir: Dir,
+  pub len: u8,
+}
+
+/// Formats as the direction's character, followed by the length. (e.g., `R3`)
+impl fmt::Display for Slide {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!( f, "{}{}", char::from( self.dir ), self.len )
+  }
+}
+
+/// Groups consecutive moves in the same direction into slides.
+///
+/// Note that, for a solution of [`solve_ita_slides`], every slide is a single
+/// move of the multi-tile metric.
+pub fn group_slides( path: &[Dir] ) -> Vec< Slide > {
+  let mut slides: Vec< Slide > = Vec::new( );
+
+  for &dir in path {
+    match slides.last_mut( ) {
+      Some( slide ) if slide.dir == dir => slide.len += 1,
+      _ => slides.push( Slide { dir, len: 1 } ),
+    }
+  }
+  slides
+}
+
+/// Like [`super::solve_ita_budget_goal`], but minimises the number of moves in
+/// the multi-tile metric. The heuristic must be admissible for that metric.
+/// (See [`SlideHeuristic`])
+///
+/// The solution contains single-tile moves, which [`group_slides`] groups
+/// into the optimal slides. As the parity argument of the single-tile metric
+/// does not hold, every iteration increases the bound by 1.
+pub fn solve_ita_slides< H: SlideHeuristic< WIDTH, HEIGHT >, G: PuzzleGrid< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: G, goal: &Goal< WIDTH, HEIGHT >, budget: Budget ) -> (SearchOutcome, SearchStats) {
+  let mut meter = BudgetMeter::new( budget );
+  let mut stats = SearchStats::default( );
+  let p = Puzzle::new( p );
+
+  let outcome =
+    if goal.is_goal( &p.grid ) { // it's solved already
+      SearchOutcome::Solved( Vec::new( ) )
+    } else {
+      let mut max_depth: u8 = h.compute_slides( &p.grid ).max( 1 );
+
+      loop {
+        let mut search = SlideSearch { h, goal, meter: &mut meter, num_steps: 0, inv_path: Vec::new( ) };
+        let result = search.dfs( &p, 0, max_depth, None );
+        let inv_path = search.inv_path;
+        let num_steps = search.num_steps;
+        meter.num_steps += num_steps;
+        stats.num_iterations += 1;
+        stats.num_last_steps = num_steps;
+
+        match result {
+          DfsResult::Solved => {
+            let path =
+              inv_path.into_iter( )
+                .flat_map( |(inv_dir, len)| std::iter::repeat_n( inv_dir.inv( ), len as usize ) )
+                .collect( );
+            break SearchOutcome::Solved( path )
+          },
+          DfsResult::OutOfBudget => break SearchOutcome::OutOfBudget,
+          DfsResult::Exhausted => { },
+        }
+
+        match max_depth.checked_add( 1 ) {
+          Some( d ) => max_depth = d,
+          None => break SearchOutcome::Exhausted,
+        }
+      }
+    };
+
+  stats.num_steps = meter.num_steps;
+  stats.elapsed = meter.elapsed( );
+  (outcome, stats)
+}
+
+/// A single depth-first iteration of [`solve_ita_slides`].
+///
+/// Unlike [`super::solve_dfs_state`], this recurses, as frames would have to
+/// track both the direction and the length of the next slide. Slide solutions
+/// are short, so the recursion is shallow.
+struct SlideSearch< 'a, H, const WIDTH: usize, const HEIGHT: usize > {
+  h: &'a H,
+  goal: &'a Goal< WIDTH, HEIGHT >,
+  meter: &'a mut BudgetMeter,
+  /// The number of steps in this iteration
+  num_steps: usize,
+  /// The slides of the gap (with "inverted" directions), up to the current
+  /// node.
+  inv_path: Vec< (Dir, u8) >,
+}
+
+impl< 'a, H: SlideHeuristic< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > SlideSearch< 'a, H, WIDTH, HEIGHT > {
+  fn dfs< G: PuzzleGrid< WIDTH, HEIGHT > >( &mut self, p: &Puzzle< G, WIDTH, HEIGHT >, depth: u8, max_depth: u8, prev_inv_dir: Option< Dir > ) -> DfsResult {
+    for inv_dir in Dir::ALL {
+      // Sliding on in the same direction would merge with the previous slide,
+      // and sliding back gives a state reachable with a shorter slide.
+      if Some( inv_dir ) == prev_inv_dir || Some( inv_dir.inv( ) ) == prev_inv_dir {
+        continue;
+      }
+
+      let mut next_p = p.clone( );
+      let mut len = 0;
+
+      while let Some( stepped_p ) = next_p.step_inv( inv_dir ) {
+        next_p = stepped_p;
+        len += 1;
+        self.num_steps += 1;
+
+        if !self.meter.allows( self.num_steps ) {
+          return DfsResult::OutOfBudget;
+        }
+
+        let hx = self.h.compute_slides( &next_p.grid ); // a lower-bound on our remaining slides
+        let gx = ( depth + 1 ).saturating_add( hx ); // a lower-bound on our total number of slides
+
+        if gx <= max_depth {
+          self.inv_path.push( (inv_dir, len) );
+
+          if hx == 0 && self.goal.is_goal( &next_p.grid ) { // we're at the solution
+            return DfsResult::Solved;
+          }
+
+          match self.dfs( &next_p, depth + 1, max_depth, Some( inv_dir ) ) {
+            DfsResult::Exhausted => { self.inv_path.pop( ); },
+            result => return result,
+          }
+        }
+      }
+    }
+    DfsResult::Exhausted
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  // stdlib imports
+  use std::collections::{HashMap, VecDeque};
+  // local imports
+  use crate::grid::{ArrayGrid, Dir, Goal};
+  use crate::heuristic::{ManhattanDistance, MaxDisplacement, SlideBound, SlideHeuristic};
+  use crate::testing::{apply_path, random_grids};
+  use super::{Budget, Puzzle, SearchOutcome, Slide, group_slides, solve_ita_slides};
+
+  /// Returns the number of slides from every grid which reaches the goal, by
+  /// breadth-first search from the goal.
+  fn slide_distances< const WIDTH: usize, const HEIGHT: usize >( goal: &Goal< WIDTH, HEIGHT > ) -> HashMap< ArrayGrid< WIDTH, HEIGHT >, u8 > {
+    let start = Puzzle::new( goal.to_grid::< ArrayGrid< WIDTH, HEIGHT > >( ) );
+    let mut distances = HashMap::from( [ (*start.grid( ), 0) ] );
+    let mut queue = VecDeque::from( [ (start, 0) ] );
+
+    while let Some( (p, distance) ) = queue.pop_front( ) {
+      for dir in Dir::ALL {
+        let mut next = p.clone( );
+        while let Some( stepped ) = next.step_inv( dir ) {
+          next = stepped;
+          if !distances.contains_key( next.grid( ) ) {
+            distances.insert( *next.grid( ), distance + 1 );
+            queue.push_back( (next.clone( ), distance + 1) );
+          }
+        }
+      }
+    }
+    distances
+  }
+
+  /// Checks the solution's slides against the exact number of slides.
+  fn check_slides< H: SlideHeuristic< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, p: ArrayGrid< WIDTH, HEIGHT >, goal: &Goal< WIDTH, HEIGHT >, distance: u8 ) {
+    assert!( h.compute_slides( &p ) <= distance, "{:?}", p );
+
+    let SearchOutcome::Solved( path ) = solve_ita_slides( h, p, goal, Budget::default( ) ).0 else {
+      panic!( "{:?} is unsolved", p );
+    };
+    assert_eq!( group_slides( &path ).len( ), distance as usize, "{:?}", p );
+    assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+  }
+
+  #[test]
+  fn slides_are_optimal( ) {
+    let goal = Goal::< 2, 3 >::solved( );
+    for (p, distance) in slide_distances( &goal ) {
+      check_slides( &MaxDisplacement::new( ), p, &goal, distance );
+      check_slides( &SlideBound( ManhattanDistance::new( ) ), p, &goal, distance );
+    }
+
+    let goal = Goal::< 3, 3 >::snake( );
+    let distances = slide_distances( &goal );
+    for p in random_grids( &goal, 20, 60, 5 ) {
+      check_slides( &MaxDisplacement::for_goal( goal ), p, &goal, distances[ &p ] );
+      check_slides( &SlideBound( ManhattanDistance::for_goal( goal ) ), p, &goal, distances[ &p ] );
+    }
+  }
+
+  #[test]
+  fn group_slides_merges_moves_in_the_same_direction( ) {
+    let path = [Dir::UP, Dir::UP, Dir::LEFT, Dir::UP, Dir::UP, Dir::UP];
+    let slides = [Slide { dir: Dir::UP, len: 2 }, Slide { dir: Dir::LEFT, len: 1 }, Slide { dir: Dir::UP, len: 3 }];
+    assert_eq!( group_slides( &path ), slides );
+    assert_eq!( group_slides( &[] ), [] );
+  }
+}
@@ -0,0 +1,311 @@
+//! A transposition table for IDA*, which remembers the states it searched.
+//! (See *Enhanced Iterative-Deepening Search* (1994), by Alexander Reinefeld
+//! and T. Anthony Marsland)
+//!
+//! Within an iteration, a state reached again at no smaller path cost is
+//! skipped, as its subtree was searched already. Across iterations, every
+//! searched state keeps its *updated* heuristic. That is, the smallest
+//! estimated cost which exceeded the bound below it, minus its path cost.
+//!
+//! The table has a fixed size (e.g., `TranspositionTable::with_memory( 256 <<
+//! 20 )`), and keys states by their Zobrist hashes. [`crate::solve_ita_table()`]
+//! uses it for the sliding puzzle, and [`crate::solve_ida_problem_table()`] for
+//! a [`HashedProblem`].
+
+// local imports
+use crate::grid::PuzzleGrid;
+use super::{GoalTest, Puzzle, SearchProblem, SlidingPuzzle, WeightedSlidingPuzzle};
+
+
+/// A [`SearchProblem`] whose states have 64-bit hashes, by which a
+/// [`TranspositionTable`] identifies them.
+pub trait HashedProblem: SearchProblem {
+  /// Equal states have equal hashes. The table assumes that distinct states
+  /// (which it remembers at once) never collide. (e.g., a Zobrist hash)
+  fn state_hash( &self, state: &Self::State ) -> u64;
+}
+
+impl< 'a, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > HashedProblem for SlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > {
+  #[inline]
+  fn state_hash( &self, p: &Puzzle< G, WIDTH, HEIGHT > ) -> u64 {
+    p.zobrist_hash( )
+  }
+}
+
+impl< 'a, G: PuzzleGrid< WIDTH, HEIGHT >, T: GoalTest< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > HashedProblem for WeightedSlidingPuzzle< 'a, G, T, WIDTH, HEIGHT > {
+  #[inline]
+  fn state_hash( &self, p: &Puzzle< G, WIDTH, HEIGHT > ) -> u64 {
+    p.zobrist_hash( )
+  }
+}
+
+/// A fixed-size table of the states searched by IDA*, indexed by their hashes.
+/// (See [`super::solve_ida_problem_table`] and [`super::solve_ita_table`])
+/// When two states share an entry, the later one replaces the other.
+///
+/// States are remembered with the automaton's state, by which the search
+/// reached them. (See [`super::PruningFsm`]) The automaton may prune other
+/// operators after other paths, so their searches differ.
+///
+/// A table may be reused by later searches, which forget all earlier states.
+#[derive(Clone, Debug)]
+pub struct TranspositionTable {
+  /// A power of two entries
+  entries: Vec< Entry >,
+  /// The current iteration. (Entries of earlier iterations are only valid
+  /// from `first_iteration`)
+  iteration: u32,
+  first_iteration: u32,
+  /// The states on the search's path, with their keys, path costs, and the
+  /// smallest estimated costs cut off below them
+  path: Vec< (u64, u32, u32) >,
+  /// The last visited state, which the search may push
+  visited: (u64, u32, u32),
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Entry {
+  key: u64,
+  /// The smallest path cost at which the state was searched
+  fx: u32,
+  /// The updated heuristic
+  hx: u32,
+  /// The iteration which searched the state (or 0, if none)
+  iteration: u32,
+  /// Whether the search is still below the state (i.e., it's on the path)
+  is_open: bool,
+}
+
+impl TranspositionTable {
+  /// The size of an entry, in bytes.
+  pub const ENTRY_SIZE: usize = size_of::< Entry >( );
+
+  /// A table with at most `num_bytes` of entries. (But at least one)
+  pub fn with_memory( num_bytes: usize ) -> Self {
+    TranspositionTable::with_capacity( num_bytes / TranspositionTable::ENTRY_SIZE )
+  }
+
+  /// A table with at most `num_entries` entries. (But at least one) Their
+  /// number is rounded down to a power of two.
+  pub fn with_capacity( num_entries: usize ) -> Self {
+    let num_entries =
+      match num_entries.checked_ilog2( ) {
+        Some( log ) => 1 << log,
+        None => 1,
+      };
+    TranspositionTable {
+      entries: vec![ Entry::default( ); num_entries ],
+      iteration: 0,
+      first_iteration: 1,
+      path: Vec::new( ),
+      visited: (0, 0, 0),
+    }
+  }
+
+  #[inline]
+  pub fn num_entries( &self ) -> usize {
+    self.entries.len( )
+  }
+
+  /// Forgets all states. (In constant time)
+  pub fn clear( &mut self ) {
+    if self.iteration == u32::MAX { // the iterations wrap around
+      self.entries.fill( Entry::default( ) );
+      self.iteration = 0;
+    }
+    self.first_iteration = self.iteration + 1;
+  }
+
+  /// Returns the number of entries with states of the current search.
+  pub fn num_used( &self ) -> usize {
+    self.entries.iter( ).filter( |e| e.iteration >= self.first_iteration ).count( )
+  }
+
+  #[inline]
+  fn key( hash: u64, fsm_state: u16 ) -> u64 {
+    hash ^ ( fsm_state as u64 + 1 ).wrapping_mul( 0x9E37_79B9_7F4A_7C15 )
+  }
+
+  #[inline]
+  fn entry_mut( &mut self, key: u64 ) -> &mut Entry {
+    let idx = key as usize & ( self.entries.len( ) - 1 );
+    &mut self.entries[ idx ]
+  }
+
+  /// Stores the last visited state, at the top of the path.
+  #[inline]
+  fn store_visited( &mut self ) {
+    let (key, fx, hx) = self.visited;
+    let iteration = self.iteration;
+    *self.entry_mut( key ) = Entry { key, fx, hx, iteration, is_open: true };
+    self.path.push( (key, fx, u32::MAX) );
+  }
+
+  /// Records a cut-off estimated cost below the top of the path.
+  #[inline]
+  fn record_cut( &mut self, gx: u32 ) {
+    if let Some( (_, _, min_cut) ) = self.path.last_mut( ) {
+      *min_cut = ( *min_cut ).min( gx );
+    }
+  }
+}
+
+/// What a depth-first search remembers about the states it searches. The
+/// search calls these in order of its path. (See
+/// [`super::problem::solve_dfs_problem`])
+pub(super) trait Transpositions< P: SearchProblem > {
+  /// Starts an iteration, from the initial state.
+  fn begin( &mut self, problem: &P, state: &P::State, hx: u32 );
+
+  /// Visits the state reached at path cost `fx`. Returns its (possibly
+  /// updated) heuristic, or `None` if its subtree need not be searched.
+  fn visit( &mut self, problem: &P, state: &P::State, fsm_state: u16, fx: u32, hx: u32 ) -> Option< u32 >;
+
+  /// The visited state is pushed onto the path.
+  fn push( &mut self );
+
+  /// The visited state is cut off, at estimated cost `gx`.
+  fn cut( &mut self, gx: u32 );
+
+  /// The top of the path is popped, after its subtree was searched.
+  fn pop( &mut self );
+}
+
+/// Remembers nothing.
+pub(super) struct NoTable;
+
+impl< P: SearchProblem > Transpositions< P > for NoTable {
+  #[inline(always)]
+  fn begin( &mut self, _problem: &P, _state: &P::State, _hx: u32 ) { }
+
+  #[inline(always)]
+  fn visit( &mut self, _problem: &P, _state: &P::State, _fsm_state: u16, _fx: u32, hx: u32 ) -> Option< u32 > {
+    Some( hx )
+  }
+
+  #[inline(always)]
+  fn push( &mut self ) { }
+
+  #[inline(always)]
+  fn cut( &mut self, _gx: u32 ) { }
+
+  #[inline(always)]
+  fn pop( &mut self ) { }
+}
+
+impl< P: HashedProblem > Transpositions< P > for TranspositionTable {
+  fn begin( &mut self, problem: &P, state: &P::State, hx: u32 ) {
+    if self.iteration == u32::MAX {
+      self.clear( );
+    }
+    self.iteration += 1;
+    self.path.clear( );
+    self.visited = (TranspositionTable::key( problem.state_hash( state ), 0 ), 0, hx);
+    self.store_visited( );
+  }
+
+  #[inline]
+  fn visit( &mut self, problem: &P, state: &P::State, fsm_state: u16, fx: u32, hx: u32 ) -> Option< u32 > {
+    let key = TranspositionTable::key( problem.state_hash( state ), fsm_state );
+    let (iteration, first_iteration) = (self.iteration, self.first_iteration);
+    let entry = *self.entry_mut( key );
+    let mut hx = hx;
+
+    // An updated heuristic only holds for paths which are no longer. (As a
+    // longer path may avoid states of a shorter one)
+    if entry.key == key && entry.iteration >= first_iteration && entry.fx <= fx {
+      if entry.iteration == iteration {
+        // Its subtree is searched already (or, it's on the path, so this is
+        // a cycle)
+        if !entry.is_open {
+          self.record_cut( fx.saturating_add( entry.hx ) );
+        }
+        return None;
+      }
+      hx = hx.max( entry.hx );
+    }
+    self.visited = (key, fx, hx);
+    Some( hx )
+  }
+
+  #[inline]
+  fn push( &mut self ) {
+    self.store_visited( );
+  }
+
+  #[inline]
+  fn cut( &mut self, gx: u32 ) {
+    self.record_cut( gx );
+  }
+
+  #[inline]
+  fn pop( &mut self ) {
+    let Some( (key, fx, min_cut) ) = self.path.pop( ) else {
+      return;
+    };
+    self.record_cut( min_cut );
+
+    let iteration = self.iteration;
+    let entry = self.entry_mut( key );
+    if entry.key == key && entry.iteration == iteration && entry.fx == fx { // it's not replaced
+      entry.hx = entry.hx.max( min_cut.saturating_sub( fx ) );
+      entry.is_open = false;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::{Board, Dir, Goal, TileCosts};
+  use crate::heuristic::{ManhattanDistance, WeightedManhattanDistance};
+  use crate::search::{Budget, PruningFsm, Puzzle, SearchOutcome, WeightedSlidingPuzzle, solve_ida_problem_table, solve_ita_table};
+  use crate::testing::{all_grids, apply_path, bfs_distances, dijkstra_costs, random_grids};
+  use super::TranspositionTable;
+
+  #[test]
+  fn tables_keep_the_optimal_length( ) {
+    let goal = Goal::< 3, 3 >::solved( );
+    let distances = bfs_distances( &goal, &Board::open( ) );
+    let h = ManhattanDistance::< 3, 3 >::new( );
+    let learned = PruningFsm::learn_sliding( 6 );
+
+    // From a single entry (which states keep replacing) to more than the
+    // states, reused by every search
+    for num_entries in [ 1, 64, 1 << 20 ] {
+      for fsm in [ &PruningFsm::SLIDING_INVERSES, &learned ] {
+        let mut table = TranspositionTable::with_capacity( num_entries );
+        for p in random_grids::< 3, 3 >( &goal, 20, 60, 9 ) {
+          let SearchOutcome::Solved( path ) = solve_ita_table( &h, p, &goal, fsm, &mut table, Budget::default( ) ).0 else {
+            panic!( "{:?} is unsolved", p );
+          };
+          assert_eq!( path.len( ) as u32, distances[ &p ], "{:?} with {} entries", p, num_entries );
+          assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn weighted_tables_keep_the_cheapest_cost( ) {
+    let goal = Goal::< 2, 3 >::solved( );
+    let board = Board::open( );
+    let costs = TileCosts::< 2, 3 >::new( &[0, 1, 3, 2, 1, 4] );
+    let problem = WeightedSlidingPuzzle::new( &goal, &board, &costs );
+    let h = WeightedManhattanDistance::new( goal, costs );
+    let expected = dijkstra_costs( &goal, &board, &costs );
+
+    let mut table = TranspositionTable::with_capacity( 256 );
+    for p in all_grids::< 2, 3 >( ) {
+      let Some( &cost ) = expected.get( &p ) else {
+        continue;
+      };
+      let SearchOutcome::Solved( ops ) = solve_ida_problem_table( &problem, &h, Puzzle::new( p ), &PruningFsm::SLIDING_INVERSES, &mut table, Budget::default( ) ).0 else {
+        panic!( "{:?} is unsolved", p );
+      };
+      // The operators move the gap, opposite to the tiles
+      let path: Vec< Dir > = ops.iter( ).map( |dir| dir.inv( ) ).collect( );
+      assert_eq!( costs.path_cost( &p, &path ), Some( cost ), "{:?}", p );
+      assert!( goal.is_goal( &apply_path( &p, &path ) ) );
+    }
+  }
+}
@@ -0,0 +1,83 @@
+// stdlib imports
+use std::io::{self, BufRead, Write};
+// local imports
+use crate::heuristic::Heuristic;
+use super::json::Json;
+use super::request::{SolveRequest, error_json};
+
+
+/// Answers JSON-lines solve requests. That is, reads one request per line from
+/// `input`, and writes one response per line to `output`. (See the module
+/// documentation for the format) Empty lines are skipped.
+///
+/// Malformed requests (including lines which are not UTF-8) produce an error
+/// response, such that the output remains aligned with the input. The output
+/// is flushed after every response.
+pub fn run_batch< H: Heuristic, R: BufRead, W: Write >( h: &H, mut input: R, mut output: W ) -> io::Result< () > {
+  let mut buf = Vec::new( );
+
+  loop {
+    buf.clear( );
+    if input.read_until( b'\n', &mut buf )? == 0 {
+      break;
+    }
+    // Like `BufRead::lines()`, strip the line ending
+    if buf.last( ) == Some( &b'\n' ) {
+      buf.pop( );
+      if buf.last( ) == Some( &b'\r' ) {
+        buf.pop( );
+      }
+    }
+
+    let line =
+      match std::str::from_utf8( &buf ) {
+        Ok( line ) => line,
+        Err( _ ) => {
+          writeln!( output, "{}", error_json( None, &"request is not valid UTF-8" ) )?;
+          output.flush( )?;
+          continue;
+        }
+      };
+
+    if line.trim( ).is_empty( ) {
+      continue;
+    }
+
+    let response =
+      match Json::parse( line ) {
+        Ok( v ) =>
+          match SolveRequest::from_json( &v ) {
+            Ok( request ) => request.solve( h ),
+            Err( err ) => error_json( v.get( "id" ).cloned( ), &err ),
+          },
+        Err( err ) => error_json( None, &err ),
+      };
+
+    writeln!( output, "{}", response )?;
+    output.flush( )?;
+  }
+
+  Ok( () )
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::heuristic::ManhattanDistance;
+  use super::run_batch;
+
+  #[test]
+  fn invalid_utf8_line_gets_an_error_response( ) {
+    let solved = "[0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24]";
+    let mut input = b"{\"id\": 1, \"puzzle\": \xff}\r\n".to_vec( );
+    input.extend_from_slice( format!( "\n{{\"id\": 2, \"puzzle\": {}}}\n", solved ).as_bytes( ) );
+
+    let mut output = Vec::new( );
+    run_batch( &ManhattanDistance::new( ), &input[..], &mut output ).unwrap( );
+    let output = String::from_utf8( output ).unwrap( );
+    let lines: Vec< &str > = output.lines( ).collect( );
+
+    assert_eq!( lines.len( ), 2 );
+    assert_eq!( lines[ 0 ], r#"{"error":"request is not valid UTF-8"}"# );
+    assert!( lines[ 1 ].starts_with( r#"{"id":2,"path":"","length":0"# ), "{}", lines[ 1 ] );
+  }
+}
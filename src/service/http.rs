@@ -0,0 +1,380 @@
+// stdlib imports
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+// local imports
+use crate::grid::ArrayGrid24;
+use crate::heuristic::Heuristic;
+use crate::search::{SearchOutcome, solve_ita_budget};
+use super::json::Json;
+use super::request::{RequestError, SolveRequest, error_json, parse_puzzle, path_string};
+
+
+/// Configuration of the HTTP service. (See [`serve()`])
+#[derive(Clone, Copy, Debug)]
+pub struct HttpConfig {
+  /// The number of requests handled concurrently
+  pub num_workers: usize,
+  /// The maximum time spent on solving for a single request. This caps the
+  /// solver's time budget. (A request may ask for less)
+  pub request_timeout: Duration,
+  /// Receives a message for every error which the service survives. (e.g., a
+  /// connection that could not be accepted) By default, they are dropped.
+  pub log: fn( &str ),
+}
+
+impl Default for HttpConfig {
+  fn default( ) -> Self {
+    HttpConfig { num_workers: 4, request_timeout: Duration::from_secs( 30 ), log: |_| { } }
+  }
+}
+
+/// Requests with larger bodies are rejected.
+const MAX_BODY_LEN: usize = 64 * 1024;
+
+/// Requests with more header lines, or more bytes in them, are rejected.
+const MAX_HEADERS: usize = 64;
+const MAX_HEADERS_LEN: usize = 16 * 1024;
+
+/// Clients that stay silent for this long are disconnected.
+const IO_TIMEOUT: Duration = Duration::from_secs( 10 );
+
+/// Clients must send their whole request within this time. (Otherwise, a client
+/// trickling bytes could hold a worker indefinitely)
+const REQUEST_DEADLINE: Duration = Duration::from_secs( 30 );
+
+/// Workers wait this long after failing to accept a connection.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis( 100 );
+
+/// Answers HTTP requests on the listener, forever. All endpoints take a JSON
+/// body with a `POST` request:
+///
+/// * `/solve` - A solve request, as in the batch mode. (See the module
+///   documentation)
+/// * `/validate` - `{"puzzle": [...]}`. Returns `{"valid": true}` or
+///   `{"valid": false, "error": "..."}`.
+/// * `/heuristic` - `{"puzzle": [...]}`. Returns `{"heuristic": 42}`.
+/// * `/hint` - `{"id": 7, "puzzle": [...], "budget": {...}}`, which is a solve
+///   request without `algorithm`. (It always searches optimal paths) Returns
+///   the first move of such a path, as `{"id": 7, "move": "U", "length": 42}`.
+///   (With a `null` move for the solved puzzle)
+///
+/// As for `/solve`, `id` is optional and echoed verbatim, and an exceeded
+/// budget produces `{"id": 7, "error": "budget exceeded"}` with status `200`.
+/// (The request was valid, but could not be answered in time) Likewise, `/hint`
+/// produces `{"id": 7, "error": "no solution within 255 moves"}` when the
+/// search finds none. (Which an admissible heuristic never causes)
+///
+/// Malformed requests produce status `400` with an `{"error": "..."}` body.
+/// (Or `413` and `431` for oversized bodies and headers, and `408` for clients
+/// that send their request too slowly)
+///
+/// The listener is intended to be bound to localhost; the service performs no
+/// authentication.
+pub fn serve< H: Heuristic + Sync >( h: &H, listener: &TcpListener, config: &HttpConfig ) -> io::Result< () > {
+  thread::scope( |s| {
+    let workers: Vec< _ > =
+      ( 0..config.num_workers.max( 1 ) )
+        .map( |_| s.spawn( || -> io::Result< () > {
+          loop {
+            let stream =
+              match listener.accept( ) {
+                Ok( (stream, _) ) => stream,
+                // Failures are usually transient (e.g., a client that reset
+                // the connection, or too many open files), so keep serving.
+                // The pause avoids spinning on persistent ones.
+                Err( err ) => {
+                  ( config.log )( &format!( "Failed to accept a connection: {}", err ) );
+                  thread::sleep( ACCEPT_RETRY_DELAY );
+                  continue;
+                },
+              };
+            // A misbehaving client only affects its own connection
+            let _ = handle_connection( h, stream, config );
+          }
+        } ) )
+        .collect( );
+
+    for w in workers {
+      w.join( ).expect( "worker panicked" )?;
+    }
+    Ok( () )
+  } )
+}
+
+/// A response with its HTTP status code.
+struct Response {
+  status: u16,
+  body: Json,
+}
+
+impl Response {
+  fn ok( body: Json ) -> Self {
+    Response { status: 200, body }
+  }
+
+  fn error( status: u16, message: &str ) -> Self {
+    Response { status, body: error_json( None, &message ) }
+  }
+}
+
+fn handle_connection< H: Heuristic >( h: &H, stream: TcpStream, config: &HttpConfig ) -> io::Result< () > {
+  stream.set_write_timeout( Some( IO_TIMEOUT ) )?;
+
+  let mut reader = BufReader::new( DeadlineReader { stream: &stream, deadline: Instant::now( ) + REQUEST_DEADLINE } );
+  let response =
+    match read_request( &mut reader ) {
+      Ok( Ok( (method, path, body) ) ) => route( h, &method, &path, &body, config ),
+      Ok( Err( response ) ) => response,
+      // (Timeouts of the socket produce `WouldBlock` on some platforms)
+      Err( err ) if matches!( err.kind( ), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock ) =>
+        Response::error( 408, "request timed out" ),
+      Err( err ) => return Err( err ),
+    };
+
+  let body = response.body.to_string( );
+  let mut stream = &stream;
+  write!(
+    stream,
+    "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+    response.status, reason( response.status ), body.len( ), body
+  )?;
+  stream.flush( )
+}
+
+/// Reads from the stream until the deadline, after which reads fail with
+/// `TimedOut`. Every read also times out after [`IO_TIMEOUT`].
+struct DeadlineReader< 'a > {
+  stream: &'a TcpStream,
+  deadline: Instant,
+}
+
+impl Read for DeadlineReader< '_ > {
+  fn read( &mut self, buf: &mut [u8] ) -> io::Result< usize > {
+    let remaining = self.deadline.saturating_duration_since( Instant::now( ) );
+    if remaining.is_zero( ) {
+      return Err( io::Error::new( io::ErrorKind::TimedOut, "request deadline passed" ) );
+    }
+    self.stream.set_read_timeout( Some( remaining.min( IO_TIMEOUT ) ) )?;
+    self.stream.read( buf )
+  }
+}
+
+/// Reads the method, path and body of a request. Returns an error response if
+/// the request is malformed, or its headers are too large.
+fn read_request< R: BufRead >( reader: &mut R ) -> io::Result< Result< (String, String, String), Response > > {
+  let mut request_line = String::new( );
+  reader.by_ref( ).take( 8 * 1024 ).read_line( &mut request_line )?;
+
+  let mut parts = request_line.split_whitespace( );
+  let (method, path) =
+    match ( parts.next( ), parts.next( ), parts.next( ) ) {
+      ( Some( method ), Some( path ), Some( version ) ) if version.starts_with( "HTTP/1." ) =>
+        ( method.to_owned( ), path.to_owned( ) ),
+      _ => return Ok( Err( Response::error( 400, "malformed request line" ) ) ),
+    };
+
+  let mut content_len = 0;
+  let mut headers_len = 0;
+  for num_headers in 0.. {
+    let mut header = String::new( );
+    let len = reader.by_ref( ).take( ( MAX_HEADERS_LEN - headers_len ) as u64 ).read_line( &mut header )?;
+    headers_len += len;
+
+    if !header.ends_with( '\n' ) {
+      // The line was cut off, by the limit or the end of the stream
+      let response =
+        if headers_len == MAX_HEADERS_LEN {
+          Response::error( 431, "request headers too large" )
+        } else {
+          Response::error( 400, "unexpected end of headers" )
+        };
+      return Ok( Err( response ) );
+    }
+
+    let header = header.trim_end( );
+    if header.is_empty( ) {
+      break;
+    } else if num_headers == MAX_HEADERS {
+      return Ok( Err( Response::error( 431, "too many request headers" ) ) );
+    }
+
+    if let Some( (name, value) ) = header.split_once( ':' ) {
+      if name.eq_ignore_ascii_case( "content-length" ) {
+        match value.trim( ).parse::< usize >( ) {
+          Ok( len ) if len <= MAX_BODY_LEN => content_len = len,
+          Ok( _ ) => return Ok( Err( Response::error( 413, "request body too large" ) ) ),
+          Err( _ ) => return Ok( Err( Response::error( 400, "malformed Content-Length" ) ) ),
+        }
+      }
+    }
+  }
+
+  let mut body = vec![0; content_len];
+  reader.read_exact( &mut body )?;
+
+  match String::from_utf8( body ) {
+    Ok( body ) => Ok( Ok( (method, path, body) ) ),
+    Err( _ ) => Ok( Err( Response::error( 400, "body is not UTF-8" ) ) ),
+  }
+}
+
+fn route< H: Heuristic >( h: &H, method: &str, path: &str, body: &str, config: &HttpConfig ) -> Response {
+  let handler: fn( &H, &Json, &HttpConfig ) -> Result< Json, RequestError > =
+    match path {
+      "/solve"     => handle_solve,
+      "/validate"  => handle_validate,
+      "/heuristic" => handle_heuristic,
+      "/hint"      => handle_hint,
+      _ => return Response::error( 404, "unknown endpoint" ),
+    };
+
+  if method != "POST" {
+    return Response::error( 405, "use POST" );
+  }
+
+  let request =
+    match Json::parse( body ) {
+      Ok( v ) => v,
+      Err( err ) => return Response::error( 400, &err.to_string( ) ),
+    };
+
+  match handler( h, &request, config ) {
+    Ok( response ) => Response::ok( response ),
+    Err( err ) => Response { status: 400, body: error_json( request.get( "id" ).cloned( ), &err ) },
+  }
+}
+
+/// Parses a solve request, where the server's timeout caps its time budget.
+fn parse_solve_request( v: &Json, config: &HttpConfig ) -> Result< SolveRequest, RequestError > {
+  let mut request = SolveRequest::from_json( v )?;
+  let max_time = request.budget.max_time.map_or( config.request_timeout, |t| t.min( config.request_timeout ) );
+  request.budget.max_time = Some( max_time );
+  Ok( request )
+}
+
+fn handle_solve< H: Heuristic >( h: &H, v: &Json, config: &HttpConfig ) -> Result< Json, RequestError > {
+  Ok( parse_solve_request( v, config )?.solve( h ) )
+}
+
+fn handle_validate< H: Heuristic >( _h: &H, v: &Json, _config: &HttpConfig ) -> Result< Json, RequestError > {
+  let puzzle = v.get( "puzzle" ).ok_or( "missing \"puzzle\"" )?;
+
+  let members =
+    match parse_puzzle( puzzle ) {
+      Ok( _ ) => vec![ ( "valid".to_owned( ), true.into( ) ) ],
+      Err( err ) => vec![ ( "valid".to_owned( ), false.into( ) ), ( "error".to_owned( ), err.0.into( ) ) ],
+    };
+  Ok( Json::Object( members ) )
+}
+
+fn handle_heuristic< H: Heuristic >( h: &H, v: &Json, _config: &HttpConfig ) -> Result< Json, RequestError > {
+  let puzzle: ArrayGrid24 = parse_puzzle( v.get( "puzzle" ).ok_or( "missing \"puzzle\"" )? )?;
+  let value = h.compute( &puzzle ) as usize;
+  Ok( Json::Object( vec![ ( "heuristic".to_owned( ), value.into( ) ) ] ) )
+}
+
+fn handle_hint< H: Heuristic >( h: &H, v: &Json, config: &HttpConfig ) -> Result< Json, RequestError > {
+  if v.get( "algorithm" ).is_some( ) {
+    return Err( "\"/hint\" always searches optimal paths, and takes no \"algorithm\"".into( ) );
+  }
+
+  let request = parse_solve_request( v, config )?;
+  let (outcome, _) = solve_ita_budget( h, request.puzzle, request.budget );
+
+  let mut members = Vec::new( );
+  if let Some( id ) = request.id {
+    members.push( ( "id".to_owned( ), id ) );
+  }
+
+  match outcome {
+    SearchOutcome::Solved( path ) => {
+      let next_move =
+        match path.first( ) {
+          Some( &dir ) => path_string( &[dir] ).into( ),
+          None => Json::Null, // it's solved already
+        };
+      members.push( ( "move".to_owned( ), next_move ) );
+      members.push( ( "length".to_owned( ), path.len( ).into( ) ) );
+    },
+    SearchOutcome::Exhausted =>
+      members.push( ( "error".to_owned( ), "no solution within 255 moves".into( ) ) ),
+    SearchOutcome::OutOfBudget =>
+      members.push( ( "error".to_owned( ), "budget exceeded".into( ) ) ),
+  }
+  Ok( Json::Object( members ) )
+}
+
+fn reason( status: u16 ) -> &'static str {
+  match status {
+    200 => "OK",
+    400 => "Bad Request",
+    404 => "Not Found",
+    405 => "Method Not Allowed",
+    408 => "Request Timeout",
+    413 => "Payload Too Large",
+    431 => "Request Header Fields Too Large",
+    _   => "Error",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::PuzzleGrid;
+  use crate::heuristic::{Heuristic, ManhattanDistance};
+  use super::{HttpConfig, MAX_HEADERS, MAX_HEADERS_LEN, read_request, route};
+
+  /// Claims that every puzzle is out of reach.
+  struct Unreachable;
+
+  impl Heuristic for Unreachable {
+    fn compute< P: PuzzleGrid >( &self, _p: &P ) -> u8 {
+      255
+    }
+  }
+
+  fn read_status( request: &str ) -> u16 {
+    match read_request( &mut request.as_bytes( ) ).unwrap( ) {
+      Ok( _ ) => 200,
+      Err( response ) => response.status,
+    }
+  }
+
+  #[test]
+  fn header_limits( ) {
+    let request = |headers: &str| format!( "POST /solve HTTP/1.1\r\n{}Content-Length: 2\r\n\r\n{{}}", headers );
+
+    assert_eq!( read_status( &request( "" ) ), 200 );
+    assert_eq!( read_status( &request( &"X: y\r\n".repeat( MAX_HEADERS - 1 ) ) ), 200 );
+    assert_eq!( read_status( &request( &"X: y\r\n".repeat( MAX_HEADERS ) ) ), 431 );
+    assert_eq!( read_status( &request( &format!( "X: {}\r\n", "y".repeat( MAX_HEADERS_LEN ) ) ) ), 431 );
+    assert_eq!( read_status( "POST /solve HTTP/1.1\r\nX: y" ), 400 );
+  }
+
+  #[test]
+  fn hint_echoes_the_id( ) {
+    let hint = |body: &str| {
+      let response = route( &ManhattanDistance::new( ), "POST", "/hint", body, &HttpConfig::default( ) );
+      ( response.status, response.body.to_string( ) )
+    };
+    let puzzle = "[1,0,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24]";
+
+    assert_eq!( hint( &format!( r#"{{"id": "a", "puzzle": {}}}"#, puzzle ) ), ( 200, r#"{"id":"a","move":"R","length":1}"#.to_owned( ) ) );
+    assert_eq!( hint( &format!( r#"{{"puzzle": {}}}"#, puzzle ) ), ( 200, r#"{"move":"R","length":1}"#.to_owned( ) ) );
+    assert_eq!(
+      hint( &format!( r#"{{"id": 1, "puzzle": {}, "budget": {{"max_nodes": 0}}}}"#, puzzle ) ),
+      ( 200, r#"{"id":1,"error":"budget exceeded"}"#.to_owned( ) ) );
+
+    let (status, body) = hint( &format!( r#"{{"id": 1, "puzzle": {}, "algorithm": "ida"}}"#, puzzle ) );
+    assert_eq!( status, 400 );
+    assert!( body.starts_with( r#"{"id":1,"error":"# ), "{}", body );
+  }
+  #[test]
+  fn hint_reports_a_missing_solution( ) {
+    let body = r#"{"id": 1, "puzzle": [1,0,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24]}"#;
+    let response = route( &Unreachable, "POST", "/hint", body, &HttpConfig::default( ) );
+    assert_eq!( response.status, 200 );
+    assert_eq!( response.body.to_string( ), r#"{"id":1,"error":"no solution within 255 moves"}"# );
+  }
+}
@@ -0,0 +1,450 @@
+//! A minimal JSON representation, with a parser and printer. It only supports
+//! what our services need, which avoids pulling in dependencies.
+
+// stdlib imports
+use std::fmt::{self, Write};
+
+
+/// A JSON value. Objects preserve the order of their members, and numbers
+/// their text. (So integers beyond the precision of `f64`, such as request ids,
+/// are echoed exactly)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+  Null,
+  Bool( bool ),
+  /// The text of a number, which follows the JSON grammar.
+  Number( String ),
+  String( String ),
+  Array( Vec< Json > ),
+  Object( Vec< (String, Json) > ),
+}
+
+/// A syntax error, at the given byte offset of the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JsonError {
+  pub offset: usize,
+}
+
+impl fmt::Display for JsonError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!( f, "invalid JSON at byte {}", self.offset )
+  }
+}
+
+impl std::error::Error for JsonError { }
+
+impl Json {
+  /// Parses a single JSON value. (Surrounding whitespace is allowed)
+  pub fn parse( s: &str ) -> Result< Json, JsonError > {
+    let mut parser = Parser { s: s.as_bytes( ), pos: 0 };
+    let v = parser.value( )?;
+    parser.skip_whitespace( );
+
+    if parser.pos == s.len( ) {
+      Ok( v )
+    } else {
+      Err( parser.error( ) )
+    }
+  }
+
+  /// Returns the member with the given key, if this is an object containing it.
+  pub fn get( &self, key: &str ) -> Option< &Json > {
+    match self {
+      Json::Object( members ) => members.iter( ).find( |(k, _)| k == key ).map( |(_, v)| v ),
+      _ => None,
+    }
+  }
+
+  pub fn as_str( &self ) -> Option< &str > {
+    match self {
+      Json::String( s ) => Some( s ),
+      _ => None,
+    }
+  }
+
+  pub fn as_array( &self ) -> Option< &[Json] > {
+    match self {
+      Json::Array( xs ) => Some( xs ),
+      _ => None,
+    }
+  }
+
+  /// Returns the number if it is a non-negative integer. (Integers written
+  /// with a fraction or exponent, like `1.0` or `1e3`, are only accepted where
+  /// `f64` represents them exactly)
+  pub fn as_u64( &self ) -> Option< u64 > {
+    /// The largest integer below which `f64` represents all integers.
+    const MAX_EXACT: f64 = ( 1u64 << f64::MANTISSA_DIGITS ) as f64;
+
+    let Json::Number( s ) = self else { return None };
+    if let Ok( x ) = s.parse::< u64 >( ) {
+      return Some( x );
+    }
+    match s.parse::< f64 >( ) {
+      Ok( x ) if x >= 0.0 && x.fract( ) == 0.0 && x <= MAX_EXACT => Some( x as u64 ),
+      _ => None,
+    }
+  }
+}
+
+impl From< &str > for Json {
+  fn from( v: &str ) -> Self {
+    Json::String( v.to_owned( ) )
+  }
+}
+
+impl From< String > for Json {
+  fn from( v: String ) -> Self {
+    Json::String( v )
+  }
+}
+
+impl From< u64 > for Json {
+  fn from( v: u64 ) -> Self {
+    Json::Number( v.to_string( ) )
+  }
+}
+
+impl From< usize > for Json {
+  fn from( v: usize ) -> Self {
+    Json::Number( v.to_string( ) )
+  }
+}
+
+impl From< bool > for Json {
+  fn from( v: bool ) -> Self {
+    Json::Bool( v )
+  }
+}
+
+/// Prints the value compactly (i.e., on a single line).
+impl fmt::Display for Json {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Json::Null => write!( f, "null" ),
+      Json::Bool( b ) => write!( f, "{}", b ),
+      Json::Number( s ) => f.write_str( s ),
+      Json::String( s ) => write_string( f, s ),
+      Json::Array( xs ) => {
+        write!( f, "[" )?;
+        for (i, x) in xs.iter( ).enumerate( ) {
+          if i > 0 {
+            write!( f, "," )?;
+          }
+          write!( f, "{}", x )?;
+        }
+        write!( f, "]" )
+      },
+      Json::Object( members ) => {
+        write!( f, "{{" )?;
+        for (i, (k, v)) in members.iter( ).enumerate( ) {
+          if i > 0 {
+            write!( f, "," )?;
+          }
+          write_string( f, k )?;
+          write!( f, ":{}", v )?;
+        }
+        write!( f, "}}" )
+      },
+    }
+  }
+}
+
+fn write_string( f: &mut fmt::Formatter<'_>, s: &str ) -> fmt::Result {
+  f.write_char( '"' )?;
+  for c in s.chars( ) {
+    match c {
+      '"'  => f.write_str( "\\\"" )?,
+      '\\' => f.write_str( "\\\\" )?,
+      '\n' => f.write_str( "\\n" )?,
+      '\r' => f.write_str( "\\r" )?,
+      '\t' => f.write_str( "\\t" )?,
+      c if ( c as u32 ) < 0x20 => write!( f, "\\u{:04x}", c as u32 )?,
+      c => f.write_char( c )?,
+    }
+  }
+  f.write_char( '"' )
+}
+
+/// A recursive-descent parser over the input bytes.
+struct Parser< 'a > {
+  s: &'a [u8],
+  pos: usize,
+}
+
+impl< 'a > Parser< 'a > {
+  /// Nested arrays/objects beyond this depth are rejected, such that malicious
+  /// input cannot overflow our stack.
+  const MAX_DEPTH: usize = 64;
+
+  fn error( &self ) -> JsonError {
+    JsonError { offset: self.pos }
+  }
+
+  fn peek( &self ) -> Option< u8 > {
+    self.s.get( self.pos ).copied( )
+  }
+
+  fn skip_whitespace( &mut self ) {
+    while let Some( b' ' | b'\t' | b'\n' | b'\r' ) = self.peek( ) {
+      self.pos += 1;
+    }
+  }
+
+  fn expect( &mut self, b: u8 ) -> Result< (), JsonError > {
+    if self.peek( ) == Some( b ) {
+      self.pos += 1;
+      Ok( () )
+    } else {
+      Err( self.error( ) )
+    }
+  }
+
+  fn expect_keyword( &mut self, keyword: &str, v: Json ) -> Result< Json, JsonError > {
+    if self.s[ self.pos.. ].starts_with( keyword.as_bytes( ) ) {
+      self.pos += keyword.len( );
+      Ok( v )
+    } else {
+      Err( self.error( ) )
+    }
+  }
+
+  fn value( &mut self ) -> Result< Json, JsonError > {
+    self.value_at_depth( 0 )
+  }
+
+  fn value_at_depth( &mut self, depth: usize ) -> Result< Json, JsonError > {
+    if depth > Self::MAX_DEPTH {
+      return Err( self.error( ) );
+    }
+
+    self.skip_whitespace( );
+    match self.peek( ) {
+      Some( b'n' ) => self.expect_keyword( "null", Json::Null ),
+      Some( b't' ) => self.expect_keyword( "true", Json::Bool( true ) ),
+      Some( b'f' ) => self.expect_keyword( "false", Json::Bool( false ) ),
+      Some( b'"' ) => Ok( Json::String( self.string( )? ) ),
+      Some( b'[' ) => {
+        self.pos += 1;
+        let mut xs = Vec::new( );
+        self.skip_whitespace( );
+
+        if self.peek( ) == Some( b']' ) {
+          self.pos += 1;
+          return Ok( Json::Array( xs ) );
+        }
+
+        loop {
+          xs.push( self.value_at_depth( depth + 1 )? );
+          self.skip_whitespace( );
+          match self.peek( ) {
+            Some( b',' ) => self.pos += 1,
+            Some( b']' ) => { self.pos += 1; return Ok( Json::Array( xs ) ); },
+            _ => return Err( self.error( ) ),
+          }
+        }
+      },
+      Some( b'{' ) => {
+        self.pos += 1;
+        let mut members = Vec::new( );
+        self.skip_whitespace( );
+
+        if self.peek( ) == Some( b'}' ) {
+          self.pos += 1;
+          return Ok( Json::Object( members ) );
+        }
+
+        loop {
+          self.skip_whitespace( );
+          let key = self.string( )?;
+          self.skip_whitespace( );
+          self.expect( b':' )?;
+          let v = self.value_at_depth( depth + 1 )?;
+          members.push( (key, v) );
+          self.skip_whitespace( );
+          match self.peek( ) {
+            Some( b',' ) => self.pos += 1,
+            Some( b'}' ) => { self.pos += 1; return Ok( Json::Object( members ) ); },
+            _ => return Err( self.error( ) ),
+          }
+        }
+      },
+      Some( b'-' | b'0'..=b'9' ) => self.number( ),
+      _ => Err( self.error( ) ),
+    }
+  }
+
+  /// Parses `-? ( 0 | [1-9][0-9]* ) ( . [0-9]+ )? ( [eE] [+-]? [0-9]+ )?`, and
+  /// keeps its text.
+  fn number( &mut self ) -> Result< Json, JsonError > {
+    let start = self.pos;
+    if self.peek( ) == Some( b'-' ) {
+      self.pos += 1;
+    }
+
+    // No leading zeros (i.e., `01`)
+    match self.peek( ) {
+      Some( b'0' ) => self.pos += 1,
+      Some( b'1'..=b'9' ) => { self.digits( ); },
+      _ => return Err( self.error( ) ),
+    }
+
+    if self.peek( ) == Some( b'.' ) {
+      self.pos += 1;
+      if self.digits( ) == 0 {
+        return Err( self.error( ) );
+      }
+    }
+
+    if let Some( b'e' | b'E' ) = self.peek( ) {
+      self.pos += 1;
+      if let Some( b'+' | b'-' ) = self.peek( ) {
+        self.pos += 1;
+      }
+      if self.digits( ) == 0 {
+        return Err( self.error( ) );
+      }
+    }
+
+    // The bytes are all ASCII, so this is valid UTF-8
+    let s = std::str::from_utf8( &self.s[ start..self.pos ] ).map_err( |_| self.error( ) )?;
+    Ok( Json::Number( s.to_owned( ) ) )
+  }
+
+  /// Skips ASCII digits, and returns how many.
+  fn digits( &mut self ) -> usize {
+    let start = self.pos;
+    while let Some( b'0'..=b'9' ) = self.peek( ) {
+      self.pos += 1;
+    }
+    self.pos - start
+  }
+
+  fn string( &mut self ) -> Result< String, JsonError > {
+    self.expect( b'"' )?;
+    let mut out = Vec::new( );
+
+    loop {
+      match self.peek( ) {
+        None => return Err( self.error( ) ),
+        Some( b'"' ) => {
+          self.pos += 1;
+          // The input is a `&str` and we only split it at ASCII characters
+          return String::from_utf8( out ).map_err( |_| self.error( ) );
+        },
+        Some( b'\\' ) => {
+          self.pos += 1;
+          let escaped =
+            match self.peek( ) {
+              Some( b'"' )  => '"',
+              Some( b'\\' ) => '\\',
+              Some( b'/' )  => '/',
+              Some( b'b' )  => '\u{8}',
+              Some( b'f' )  => '\u{c}',
+              Some( b'n' )  => '\n',
+              Some( b'r' )  => '\r',
+              Some( b't' )  => '\t',
+              Some( b'u' )  => {
+                self.pos += 1;
+                let hi = self.hex4( )?;
+                // Characters outside the BMP are encoded as surrogate pairs
+                let c =
+                  if ( 0xD800..0xDC00 ).contains( &hi ) {
+                    self.expect( b'\\' )?;
+                    self.expect( b'u' )?;
+                    let lo = self.hex4( )?;
+                    if !( 0xDC00..0xE000 ).contains( &lo ) {
+                      return Err( self.error( ) );
+                    }
+                    0x10000 + ( ( hi - 0xD800 ) << 10 ) + ( lo - 0xDC00 )
+                  } else {
+                    hi
+                  };
+                let c = char::from_u32( c ).ok_or_else( || self.error( ) )?;
+                let mut buf = [0u8; 4];
+                out.extend_from_slice( c.encode_utf8( &mut buf ).as_bytes( ) );
+                continue;
+              },
+              _ => return Err( self.error( ) ),
+            };
+          self.pos += 1;
+          let mut buf = [0u8; 4];
+          out.extend_from_slice( escaped.encode_utf8( &mut buf ).as_bytes( ) );
+        },
+        Some( b ) if b < 0x20 => return Err( self.error( ) ),
+        Some( b ) => {
+          self.pos += 1;
+          out.push( b );
+        },
+      }
+    }
+  }
+
+  fn hex4( &mut self ) -> Result< u32, JsonError > {
+    let digits = self.s.get( self.pos..self.pos + 4 ).ok_or_else( || self.error( ) )?;
+    let mut v = 0;
+
+    for &d in digits {
+      // Note that `u32::from_str_radix` would also accept a leading `+`
+      let d = ( d as char ).to_digit( 16 ).ok_or_else( || self.error( ) )?;
+      v = v * 16 + d;
+    }
+    self.pos += 4;
+    Ok( v )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Json, JsonError};
+
+  #[test]
+  fn numbers_keep_their_text( ) {
+    for s in [ "0", "-0", "7", "-12", "9007199254740993", "18446744073709551616", "1.5", "-0.25", "1e5", "1E+5", "2.5e-3" ] {
+      let v = Json::parse( s ).unwrap( );
+      assert_eq!( v, Json::Number( s.to_owned( ) ) );
+      assert_eq!( v.to_string( ), s );
+    }
+
+    let v = Json::parse( r#"{"id": 9007199254740993}"# ).unwrap( );
+    assert_eq!( v.to_string( ), r#"{"id":9007199254740993}"# );
+    assert_eq!( v.get( "id" ).and_then( Json::as_u64 ), Some( 9007199254740993 ) );
+  }
+
+  #[test]
+  fn invalid_numbers_are_rejected( ) {
+    for s in [ "01", "-01", "1.", "1.e5", "-.5", ".5", "-", "+1", "1e", "1e+", "0x10", "1.5.5", "--1" ] {
+      assert!( Json::parse( s ).is_err( ), "{}", s );
+    }
+    assert_eq!( Json::parse( "[1, 01]" ), Err( JsonError { offset: 5 } ) );
+  }
+
+  #[test]
+  fn as_u64( ) {
+    let as_u64 = |s: &str| Json::parse( s ).unwrap( ).as_u64( );
+    assert_eq!( as_u64( "18446744073709551615" ), Some( u64::MAX ) );
+    assert_eq!( as_u64( "18446744073709551616" ), None );
+    assert_eq!( as_u64( "1e3" ), Some( 1000 ) );
+    assert_eq!( as_u64( "2.0" ), Some( 2 ) );
+    assert_eq!( as_u64( "-0" ), Some( 0 ) );
+    assert_eq!( as_u64( "1.5" ), None );
+    assert_eq!( as_u64( "-1" ), None );
+    assert_eq!( as_u64( "1e300" ), None );
+    assert_eq!( as_u64( "\"1\"" ), None );
+  }
+
+  #[test]
+  fn values( ) {
+    let v = Json::parse( " {\"a\": [null, true, false, \"x\\n\\u00e9\\ud83d\\ude00\"], \"b\": {}} " ).unwrap( );
+    assert_eq!( v, Json::Object( vec![
+        ( "a".to_owned( ), Json::Array( vec![ Json::Null, Json::Bool( true ), Json::Bool( false ), Json::String( "x\né😀".to_owned( ) ) ] ) )
+      , ( "b".to_owned( ), Json::Object( vec![ ] ) )
+      ] ) );
+    assert_eq!( v.to_string( ), "{\"a\":[null,true,false,\"x\\né😀\"],\"b\":{}}" );
+
+    for s in [ "", "nul", "[1,]", "{\"a\" 1}", "{1: 2}", "\"\\ud83d\"", "\"\\x\"", "[] []" ] {
+      assert!( Json::parse( s ).is_err( ), "{}", s );
+    }
+    assert!( Json::parse( &"[".repeat( 100 ) ).is_err( ) );
+  }
+}
@@ -0,0 +1,35 @@
+//! Long-running services around the solver. These load the (expensive)
+//! heuristic once, and subsequently answer many requests. Either as JSON lines
+//! (see [`run_batch()`]) or over HTTP (see [`serve()`]).
+//!
+//! Requests and responses are JSON objects. A solve request looks as follows:
+//!
+//! ```json
+//! { "id": 7
+//! , "puzzle": [17,1,20,9,16, 2,22,19,14,5, 15,21,0,3,24, 23,18,13,12,7, 10,8,6,4,11]
+//! , "algorithm": "ida"
+//! , "budget": { "max_nodes": 1000000000, "max_millis": 60000 }
+//! }
+//! ```
+//!
+//! Only `puzzle` is mandatory. `algorithm` is either `"ida"` (the default) or
+//! `"dfs"`, which additionally requires `"max_depth"`. `id` is copied to the
+//! response verbatim. The response is either:
+//!
+//! ```json
+//! { "id": 7, "path": "ULDR...", "length": 100, "stats": { "nodes": 1234, "iterations": 50, "millis": 789 } }
+//! { "id": 7, "error": "budget exceeded", "stats": { ... } }
+//! ```
+//!
+//! where `path` contains the directions in which the tiles move. (See
+//! [`crate::solve_ita()`])
+
+mod json;
+mod request;
+mod batch;
+mod http;
+
+pub use json::{Json, JsonError};
+pub use request::{Algorithm, RequestError, SolveRequest, parse_puzzle};
+pub use batch::run_batch;
+pub use http::{HttpConfig, serve};
@@ -0,0 +1,157 @@
+// stdlib imports
+use std::fmt;
+use std::time::Duration;
+// local imports
+use crate::grid::{ArrayGrid24, Dir, validate_cells};
+use crate::heuristic::Heuristic;
+use crate::search::{Budget, SearchOutcome, SearchStats, solve_dfs_budget, solve_ita_budget};
+use super::json::Json;
+
+
+/// A malformed request. The message is sent back to the client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestError( pub String );
+
+impl fmt::Display for RequestError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str( &self.0 )
+  }
+}
+
+impl std::error::Error for RequestError { }
+
+impl From< &str > for RequestError {
+  fn from( v: &str ) -> Self {
+    RequestError( v.to_owned( ) )
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+  /// Iterative-deepening A* (see [`crate::solve_ita()`]). Finds optimal paths.
+  Ida,
+  /// A single depth-first iteration (see [`crate::solve_dfs()`]).
+  Dfs { max_depth: u8 },
+}
+
+/// A request to solve a single puzzle. (See the module documentation for its
+/// JSON representation)
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolveRequest {
+  /// Arbitrary JSON that is echoed in the response
+  pub id: Option< Json >,
+  pub puzzle: ArrayGrid24,
+  pub algorithm: Algorithm,
+  pub budget: Budget,
+}
+
+/// Parses a JSON array of 25 tiles (with `0` as the gap) into a valid and
+/// solvable puzzle.
+pub fn parse_puzzle( v: &Json ) -> Result< ArrayGrid24, RequestError > {
+  let tiles = v.as_array( ).ok_or( "\"puzzle\" must be an array" )?;
+
+  if tiles.len( ) != 25 {
+    return Err( "\"puzzle\" must contain 25 tiles".into( ) );
+  }
+
+  let mut cells = [0u8; 25];
+  for (cell, tile) in cells.iter_mut( ).zip( tiles ) {
+    *cell =
+      tile.as_u64( )
+        .and_then( |t| u8::try_from( t ).ok( ) )
+        .ok_or( "\"puzzle\" must contain integer tiles" )?;
+  }
+
+  validate_cells( &cells ).map_err( |err| RequestError( err.to_string( ) ) )?;
+  Ok( ArrayGrid24::from( cells ) )
+}
+
+impl SolveRequest {
+  pub fn from_json( v: &Json ) -> Result< Self, RequestError > {
+    if !matches!( v, Json::Object( _ ) ) {
+      return Err( "request must be an object".into( ) );
+    }
+
+    let id = v.get( "id" ).cloned( );
+    let puzzle = parse_puzzle( v.get( "puzzle" ).ok_or( "missing \"puzzle\"" )? )?;
+
+    let algorithm =
+      match v.get( "algorithm" ).map( |a| a.as_str( ).ok_or( "\"algorithm\" must be a string" ) ).transpose( )? {
+        None | Some( "ida" ) => Algorithm::Ida,
+        Some( "dfs" ) => {
+          let max_depth =
+            v.get( "max_depth" )
+              .and_then( Json::as_u64 )
+              .and_then( |d| u8::try_from( d ).ok( ) )
+              .ok_or( "\"dfs\" requires an integer \"max_depth\" below 256" )?;
+          Algorithm::Dfs { max_depth }
+        },
+        Some( _ ) => return Err( "unknown \"algorithm\"".into( ) ),
+      };
+
+    let mut budget = Budget::default( );
+    if let Some( b ) = v.get( "budget" ) {
+      if let Some( n ) = b.get( "max_nodes" ) {
+        let n = n.as_u64( ).ok_or( "\"max_nodes\" must be a non-negative integer" )?;
+        budget.max_steps = Some( usize::try_from( n ).unwrap_or( usize::MAX ) );
+      }
+      if let Some( t ) = b.get( "max_millis" ) {
+        let t = t.as_u64( ).ok_or( "\"max_millis\" must be a non-negative integer" )?;
+        budget.max_time = Some( Duration::from_millis( t ) );
+      }
+    }
+
+    Ok( SolveRequest { id, puzzle, algorithm, budget } )
+  }
+
+  /// Solves the puzzle, and produces the JSON response.
+  pub fn solve< H: Heuristic >( &self, h: &H ) -> Json {
+    let (outcome, stats) =
+      match self.algorithm {
+        Algorithm::Ida => solve_ita_budget( h, self.puzzle, self.budget ),
+        Algorithm::Dfs { max_depth } => solve_dfs_budget( h, self.puzzle, max_depth, self.budget ),
+      };
+
+    let mut members = Vec::new( );
+    if let Some( id ) = &self.id {
+      members.push( ( "id".to_owned( ), id.clone( ) ) );
+    }
+
+    match outcome {
+      SearchOutcome::Solved( path ) => {
+        members.push( ( "path".to_owned( ), Json::String( path_string( &path ) ) ) );
+        members.push( ( "length".to_owned( ), path.len( ).into( ) ) );
+      },
+      SearchOutcome::Exhausted =>
+        members.push( ( "error".to_owned( ), "no solution within max_depth".into( ) ) ),
+      SearchOutcome::OutOfBudget =>
+        members.push( ( "error".to_owned( ), "budget exceeded".into( ) ) ),
+    }
+
+    members.push( ( "stats".to_owned( ), stats_json( &stats ) ) );
+    Json::Object( members )
+  }
+}
+
+/// Produces the response for a request that could not be handled.
+pub fn error_json( id: Option< Json >, err: &dyn fmt::Display ) -> Json {
+  let mut members = Vec::new( );
+  if let Some( id ) = id {
+    members.push( ( "id".to_owned( ), id ) );
+  }
+  members.push( ( "error".to_owned( ), err.to_string( ).into( ) ) );
+  Json::Object( members )
+}
+
+/// Represents the path as a string of `U`, `R`, `D` and `L` characters.
+pub fn path_string( path: &[Dir] ) -> String {
+  path.iter( ).map( |&d| char::from( d ) ).collect( )
+}
+
+fn stats_json( stats: &SearchStats ) -> Json {
+  Json::Object( vec![
+    ( "nodes".to_owned( ), stats.num_steps.into( ) )
+  , ( "iterations".to_owned( ), stats.num_iterations.into( ) )
+  , ( "millis".to_owned( ), ( stats.elapsed.as_millis( ) as u64 ).into( ) )
+  ] )
+}
@@ -0,0 +1,119 @@
+//! Brute-force references for the tests, on boards small enough to enumerate.
+
+// stdlib imports
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+// local imports
+use crate::grid::{ArrayGrid, Board, Dir, Goal, PuzzleGrid, TileCosts};
+use crate::search::Puzzle;
+use crate::verify::{SplitMix64, random_step};
+
+
+/// Returns the number of moves from every grid which reaches the goal on the
+/// board, by breadth-first search from the goal. (Moves are reversible)
+pub(crate) fn bfs_distances< const WIDTH: usize, const HEIGHT: usize >( goal: &Goal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT > ) -> HashMap< ArrayGrid< WIDTH, HEIGHT >, u32 > {
+  bfs_distances_from( &[ goal.to_grid( ) ], board )
+}
+
+/// Like [`bfs_distances()`], but returns the number of moves to the nearest of
+/// the `starts`.
+pub(crate) fn bfs_distances_from< const WIDTH: usize, const HEIGHT: usize >( starts: &[ArrayGrid< WIDTH, HEIGHT >], board: &Board< WIDTH, HEIGHT > ) -> HashMap< ArrayGrid< WIDTH, HEIGHT >, u32 > {
+  let mut distances: HashMap< ArrayGrid< WIDTH, HEIGHT >, u32 > = starts.iter( ).map( |&grid| (grid, 0) ).collect( );
+  let mut queue: VecDeque< (Puzzle< ArrayGrid< WIDTH, HEIGHT >, WIDTH, HEIGHT >, u32) > = starts.iter( ).map( |&grid| (Puzzle::new( grid ), 0) ).collect( );
+
+  while let Some( (p, distance) ) = queue.pop_front( ) {
+    for dir in Dir::ALL {
+      if let Some( next ) = p.step_inv_on( dir, board ) {
+        if !distances.contains_key( next.grid( ) ) {
+          distances.insert( *next.grid( ), distance + 1 );
+          queue.push_back( (next, distance + 1) );
+        }
+      }
+    }
+  }
+  distances
+}
+
+/// Like [`bfs_distances()`], but returns the minimal total cost of the moves,
+/// where moving a tile costs [`TileCosts::cost()`]. (By Dijkstra's algorithm)
+pub(crate) fn dijkstra_costs< const WIDTH: usize, const HEIGHT: usize >( goal: &Goal< WIDTH, HEIGHT >, board: &Board< WIDTH, HEIGHT >, costs: &TileCosts< WIDTH, HEIGHT > ) -> HashMap< ArrayGrid< WIDTH, HEIGHT >, u32 > {
+  let start = goal.to_grid::< ArrayGrid< WIDTH, HEIGHT > >( );
+  let mut distances = HashMap::from( [ (start, 0) ] );
+  // Grids are unordered, so the queue refers to them by index
+  let mut grids = vec![ start ];
+  let mut queue = BinaryHeap::from( [ Reverse( (0, 0) ) ] );
+
+  while let Some( Reverse( (distance, grid_idx) ) ) = queue.pop( ) {
+    let grid = grids[ grid_idx ];
+    if distances[ &grid ] < distance {
+      continue;
+    }
+
+    let p = Puzzle::new( grid );
+    for dir in Dir::ALL {
+      if let Some( next ) = p.step_inv_on( dir, board ) {
+        // The moved tile is in the gap's previous cell
+        let moved_tile = next.grid( ).get_tile( grid.find_gap( ) );
+        let next_distance = distance + costs.cost( moved_tile ) as u32;
+
+        if distances.get( next.grid( ) ).is_none_or( |&d| next_distance < d ) {
+          distances.insert( *next.grid( ), next_distance );
+          queue.push( Reverse( (next_distance, grids.len( )) ) );
+          grids.push( *next.grid( ) );
+        }
+      }
+    }
+  }
+  distances
+}
+
+/// Returns the grids at the ends of random walks from the goal, of `walk_len`
+/// moves each.
+pub(crate) fn random_grids< const WIDTH: usize, const HEIGHT: usize >( goal: &Goal< WIDTH, HEIGHT >, num_walks: usize, walk_len: usize, seed: u64 ) -> Vec< ArrayGrid< WIDTH, HEIGHT > > {
+  let mut rng = SplitMix64( seed );
+
+  ( 0..num_walks ).map( |_| {
+    let mut p = Puzzle::new( goal.to_grid::< ArrayGrid< WIDTH, HEIGHT > >( ) );
+    for _ in 0..walk_len {
+      p = random_step( &p, &mut rng );
+    }
+    *p.grid( )
+  } ).collect( )
+}
+
+/// Moves the tiles along the path (see [`crate::DfsStack::path()`]), and
+/// returns the grid reached.
+pub(crate) fn apply_path< const WIDTH: usize, const HEIGHT: usize >( grid: &ArrayGrid< WIDTH, HEIGHT >, path: &[Dir] ) -> ArrayGrid< WIDTH, HEIGHT > {
+  let mut p = Puzzle::new( *grid );
+  for dir in path {
+    assert!( p.step_inv_mut( dir.inv( ) ), "the path moves within the grid" );
+  }
+  *p.grid( )
+}
+
+/// Returns every permutation of [0..n-1].
+pub(crate) fn permutations( n: usize ) -> Vec< Vec< u8 > > {
+  if n == 0 {
+    return vec![ Vec::new( ) ];
+  }
+
+  let mut result = Vec::new( );
+  for p in permutations( n - 1 ) {
+    // Insert the largest element at every position
+    for i in 0..n {
+      let mut next = p.clone( );
+      next.insert( i, ( n - 1 ) as u8 );
+      result.push( next );
+    }
+  }
+  result
+}
+
+/// Returns every arrangement of the tiles. (Solvable or not)
+pub(crate) fn all_grids< const WIDTH: usize, const HEIGHT: usize >( ) -> Vec< ArrayGrid< WIDTH, HEIGHT > > {
+  permutations( WIDTH * HEIGHT ).iter( ).map( |tiles| {
+    let mut rows = [[0; WIDTH]; HEIGHT];
+    rows.as_flattened_mut( ).copy_from_slice( tiles );
+    ArrayGrid::from( rows )
+  } ).collect( )
+}
@@ -0,0 +1,373 @@
+//! Checks heuristics for admissibility and consistency on samples of the
+//! search space, before trusting their solutions. (See [`verify_heuristic()`])
+//! No sample proves a heuristic correct, but every counterexample proves it
+//! wrong.
+//!
+//! Exact distances come from a breadth-first search near the goal, and from
+//! IDA* with the [`crate::ManhattanDistance`] (which is admissible) on random
+//! instances. Consistency is checked along random walks from the goal, whose
+//! moves may change the heuristic by at most 1. (Which only counts as a
+//! violation if it claims to be consistent; see
+//! [`crate::Heuristic::is_consistent()`]) Either way, the [`VerifyReport`]
+//! counts those moves and their largest change, next to the violations and
+//! their first counterexamples.
+
+// stdlib imports
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt;
+// local imports
+use crate::grid::{ArrayGrid, Dir, Goal, PuzzleCell, PuzzleGrid, PuzzleTile};
+use crate::heuristic::{Heuristic, ManhattanDistance};
+use crate::search::{Budget, Puzzle, SearchOutcome, solve_ita_budget_goal};
+
+
+/// What [`verify_heuristic()`] samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyConfig {
+  /// The number of random walks from the goal, along which every move is
+  /// checked for consistency
+  pub num_walks: usize,
+  /// The number of moves of every random walk
+  pub walk_len: usize,
+  /// All grids within this distance from the goal are checked against their
+  /// exact distances, and all moves between them for consistency. (Their
+  /// number grows exponentially)
+  pub max_bfs_distance: u8,
+  /// The number of random instances which are solved optimally, along whose
+  /// solutions all grids are checked against their exact distances
+  pub num_samples: usize,
+  /// The number of random moves from the goal to every instance
+  pub sample_walk_len: usize,
+  /// The budget of every instance's search. (Unsolved instances are skipped)
+  pub sample_budget: Budget,
+  /// The maximum number of reported counterexamples. (All are counted)
+  pub max_counterexamples: usize,
+  /// The seed of the random walks, which are repeated for equal seeds
+  pub seed: u64,
+}
+
+impl Default for VerifyConfig {
+  fn default( ) -> Self {
+    VerifyConfig {
+      num_walks: 100,
+      walk_len: 1000,
+      max_bfs_distance: 12,
+      num_samples: 10,
+      sample_walk_len: 40,
+      sample_budget: Budget { max_steps: Some( 10_000_000 ), max_time: None },
+      max_counterexamples: 10,
+      seed: 24,
+    }
+  }
+}
+
+/// A grid (or move) on which a heuristic is wrong.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Counterexample< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// The heuristic overestimates the exact distance to the goal.
+  Inadmissible { grid: ArrayGrid< WIDTH, HEIGHT >, hx: u8, distance: usize },
+  /// A move (from `from` into `to`) changes the heuristic by more than 1,
+  /// while it claims to be consistent. (See [`Heuristic::is_consistent()`])
+  Inconsistent { from: ArrayGrid< WIDTH, HEIGHT >, from_hx: u8, to: ArrayGrid< WIDTH, HEIGHT >, to_hx: u8 },
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > fmt::Display for Counterexample< WIDTH, HEIGHT > {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Counterexample::Inadmissible { grid, hx, distance } => {
+        writeln!( f, "h = {} exceeds the distance {} of:", hx, distance )?;
+        fmt_rows( f, grid )
+      },
+      Counterexample::Inconsistent { from, from_hx, to, to_hx } => {
+        writeln!( f, "h = {} in:", from_hx )?;
+        fmt_rows( f, from )?;
+        writeln!( f, "but h = {} after a move, in:", to_hx )?;
+        fmt_rows( f, to )
+      },
+    }
+  }
+}
+
+/// Writes the grid as rows, with the gap as `_`.
+fn fmt_rows< const WIDTH: usize, const HEIGHT: usize >( f: &mut fmt::Formatter<'_>, grid: &ArrayGrid< WIDTH, HEIGHT > ) -> fmt::Result {
+  let width = ( PuzzleCell::< WIDTH, HEIGHT >::NUM_CELLS - 1 ).to_string( ).len( );
+
+  for row in grid.to_rows( ) {
+    for (x, tile) in row.into_iter( ).enumerate( ) {
+      let sep = if x == 0 { "" } else { " " };
+
+      if tile == u8::from( PuzzleTile::< WIDTH, HEIGHT >::GAP ) {
+        write!( f, "{}{:>width$}", sep, "_" )?;
+      } else {
+        write!( f, "{}{:>width$}", sep, tile )?;
+      }
+    }
+    writeln!( f )?;
+  }
+  Ok( () )
+}
+
+/// The outcome of [`verify_heuristic()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyReport< const WIDTH: usize = 5, const HEIGHT: usize = 5 > {
+  /// The number of moves checked for consistency
+  pub num_moves: usize,
+  /// The number of moves which change the heuristic by more than 1. These are
+  /// only violations if the heuristic claims to be consistent. (Otherwise,
+  /// [`crate::solve_ita_bpmx`] applies BPMX; see [`Heuristic::is_consistent()`])
+  pub num_inconsistent_moves: usize,
+  /// The largest change of the heuristic by a single move
+  pub max_move_change: u8,
+  /// The number of grids checked against their exact distances
+  pub num_grids: usize,
+  /// The number of instances which were not solved within the budget
+  pub num_unsolved_samples: usize,
+  /// The number of all counterexamples found
+  pub num_violations: usize,
+  /// The first counterexamples found
+  pub counterexamples: Vec< Counterexample< WIDTH, HEIGHT > >,
+}
+
+impl< const WIDTH: usize, const HEIGHT: usize > VerifyReport< WIDTH, HEIGHT > {
+  /// Whether no counterexample was found.
+  #[inline]
+  pub fn is_ok( &self ) -> bool {
+    self.num_violations == 0
+  }
+}
+
+/// Checks the heuristic for the distances to the solved puzzle. (See
+/// [`verify_heuristic_goal()`])
+pub fn verify_heuristic< H: Heuristic< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, config: &VerifyConfig ) -> VerifyReport< WIDTH, HEIGHT > {
+  verify_heuristic_goal( h, &Goal::solved( ), config )
+}
+
+/// Checks the heuristic for the distances to the goal:
+///
+/// * Every move along random walks from the goal (and between the grids near
+///   the goal) changes it by at most 1. The report counts the moves which
+///   change it by more, and their largest change. They are only violations if
+///   the heuristic claims to be consistent.
+/// * It never exceeds the exact distance of the grids near the goal, found by
+///   breadth-first search, nor of those along optimal solutions of random
+///   instances, found by IDA*.
+pub fn verify_heuristic_goal< H: Heuristic< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize >( h: &H, goal: &Goal< WIDTH, HEIGHT >, config: &VerifyConfig ) -> VerifyReport< WIDTH, HEIGHT > {
+  let mut verifier =
+    Verifier {
+      h,
+      is_consistent: h.is_consistent( ),
+      max_counterexamples: config.max_counterexamples,
+      report:
+        VerifyReport {
+          num_moves: 0,
+          num_inconsistent_moves: 0,
+          max_move_change: 0,
+          num_grids: 0,
+          num_unsolved_samples: 0,
+          num_violations: 0,
+          counterexamples: Vec::new( ),
+        },
+    };
+  let mut rng = SplitMix64( config.seed );
+  let start = Puzzle::new( goal.to_grid::< ArrayGrid< WIDTH, HEIGHT > >( ) );
+
+  // Consistency along random walks
+  for _ in 0..config.num_walks {
+    let mut p = start.clone( );
+    let mut hx = h.compute( p.grid( ) );
+
+    for _ in 0..config.walk_len {
+      let next = random_step( &p, &mut rng );
+      let next_hx = h.compute( next.grid( ) );
+      verifier.check_move( &p, hx, &next, next_hx );
+      (p, hx) = (next, next_hx);
+    }
+  }
+
+  // Exact distances near the goal. Every move changes the distance by exactly
+  // 1 (as moves change the parity), so we check the moves into the next layer.
+  let mut distances: HashMap< ArrayGrid< WIDTH, HEIGHT >, u8 > = HashMap::from( [ (*start.grid( ), 0) ] );
+  let mut layer = vec![ start.clone( ) ];
+
+  for distance in 0..=config.max_bfs_distance {
+    let mut next_layer = Vec::new( );
+
+    for p in &layer {
+      let hx = h.compute( p.grid( ) );
+      verifier.check_distance( p, hx, distance as usize );
+
+      if distance < config.max_bfs_distance {
+        for dir in Dir::ALL {
+          let Some( next ) = p.step_inv( dir ) else {
+            continue;
+          };
+          let is_new =
+            match distances.entry( *next.grid( ) ) {
+              Entry::Vacant( e ) => {
+                e.insert( distance + 1 );
+                true
+              },
+              Entry::Occupied( e ) if *e.get( ) == distance + 1 => false,
+              Entry::Occupied( _ ) => continue,
+            };
+          verifier.check_move( p, hx, &next, h.compute( next.grid( ) ) );
+
+          if is_new {
+            next_layer.push( next );
+          }
+        }
+      }
+    }
+    layer = next_layer;
+  }
+
+  // Exact distances along optimal solutions
+  let reference = ManhattanDistance::for_goal( *goal );
+
+  for _ in 0..config.num_samples {
+    let mut p = start.clone( );
+    for _ in 0..config.sample_walk_len {
+      p = random_step( &p, &mut rng );
+    }
+
+    match solve_ita_budget_goal( &reference, *p.grid( ), goal, config.sample_budget ).0 {
+      SearchOutcome::Solved( path ) => {
+        for (i, dir) in path.iter( ).enumerate( ) {
+          verifier.check_distance( &p, h.compute( p.grid( ) ), path.len( ) - i );
+          p = p.step_inv( dir.inv( ) ).expect( "the solution moves within the grid" );
+        }
+        verifier.check_distance( &p, h.compute( p.grid( ) ), 0 );
+      },
+      _ => verifier.report.num_unsolved_samples += 1,
+    }
+  }
+  verifier.report
+}
+
+/// Collects the counterexamples.
+struct Verifier< 'a, H, const WIDTH: usize, const HEIGHT: usize > {
+  h: &'a H,
+  is_consistent: bool,
+  max_counterexamples: usize,
+  report: VerifyReport< WIDTH, HEIGHT >,
+}
+
+impl< 'a, H: Heuristic< WIDTH, HEIGHT >, const WIDTH: usize, const HEIGHT: usize > Verifier< 'a, H, WIDTH, HEIGHT > {
+  fn check_move( &mut self, from: &Puzzle< ArrayGrid< WIDTH, HEIGHT >, WIDTH, HEIGHT >, from_hx: u8, to: &Puzzle< ArrayGrid< WIDTH, HEIGHT >, WIDTH, HEIGHT >, to_hx: u8 ) {
+    let change = from_hx.abs_diff( to_hx );
+    self.report.num_moves += 1;
+    self.report.max_move_change = self.report.max_move_change.max( change );
+
+    if change > 1 {
+      self.report.num_inconsistent_moves += 1;
+
+      if self.is_consistent {
+        self.add( Counterexample::Inconsistent { from: *from.grid( ), from_hx, to: *to.grid( ), to_hx } );
+      }
+    }
+  }
+
+  fn check_distance( &mut self, p: &Puzzle< ArrayGrid< WIDTH, HEIGHT >, WIDTH, HEIGHT >, hx: u8, distance: usize ) {
+    debug_assert!( hx == self.h.compute( p.grid( ) ) );
+    self.report.num_grids += 1;
+
+    if hx as usize > distance {
+      self.add( Counterexample::Inadmissible { grid: *p.grid( ), hx, distance } );
+    }
+  }
+
+  fn add( &mut self, counterexample: Counterexample< WIDTH, HEIGHT > ) {
+    self.report.num_violations += 1;
+
+    if self.report.counterexamples.len( ) < self.max_counterexamples {
+      self.report.counterexamples.push( counterexample );
+    }
+  }
+}
+
+/// Moves the gap in a random direction (among those within the grid).
+pub(crate) fn random_step< const WIDTH: usize, const HEIGHT: usize >( p: &Puzzle< ArrayGrid< WIDTH, HEIGHT >, WIDTH, HEIGHT >, rng: &mut SplitMix64 ) -> Puzzle< ArrayGrid< WIDTH, HEIGHT >, WIDTH, HEIGHT > {
+  loop {
+    if let Some( next ) = p.step_inv( Dir::ALL[ ( rng.next( ) % 4 ) as usize ] ) {
+      return next;
+    }
+  }
+}
+
+/// A small pseudo-random generator, which is good enough for random walks.
+/// (See *Fast splittable pseudorandom number generators* (2014), by Guy L.
+/// Steele, Doug Lea and Christine H. Flood)
+pub(crate) struct SplitMix64( pub u64 );
+
+impl SplitMix64 {
+  pub fn next( &mut self ) -> u64 {
+    self.0 = self.0.wrapping_add( 0x9E37_79B9_7F4A_7C15 );
+    let mut z = self.0;
+    z = ( z ^ ( z >> 30 ) ).wrapping_mul( 0xBF58_476D_1CE4_E5B9 );
+    z = ( z ^ ( z >> 27 ) ).wrapping_mul( 0x94D0_49BB_1331_11EB );
+    z ^ ( z >> 31 )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::grid::PuzzleGrid;
+  use crate::heuristic::{Heuristic, ManhattanDistance, Max, PatternDB};
+  use super::{Counterexample, VerifyConfig, verify_heuristic};
+
+  /// Twice the Manhattan distance, which overestimates.
+  struct DoubleManhattan( ManhattanDistance< 3, 3 > );
+
+  impl Heuristic< 3, 3 > for DoubleManhattan {
+    fn compute< P: PuzzleGrid< 3, 3 > >( &self, p: &P ) -> u8 {
+      2 * self.0.compute( p )
+    }
+  }
+
+  /// A pattern DB which falsely claims to be consistent.
+  struct ClaimsConsistent( PatternDB< 3, 3 > );
+
+  impl Heuristic< 3, 3 > for ClaimsConsistent {
+    fn compute< P: PuzzleGrid< 3, 3 > >( &self, p: &P ) -> u8 {
+      self.0.compute( p )
+    }
+  }
+
+  fn pattern_db( ) -> PatternDB< 3, 3 > {
+    PatternDB::build( &[ &[1,2,3,4], &[5,6,7,8] ] )
+  }
+
+  #[test]
+  fn admissible_heuristic_is_ok( ) {
+    let report = verify_heuristic( &Max( ManhattanDistance::< 3, 3 >::new( ), pattern_db( ) ), &VerifyConfig::default( ) );
+
+    assert!( report.is_ok( ), "{:?}", report.counterexamples );
+    assert!( report.num_moves > 0 && report.num_grids > 0 );
+    assert_eq!( report.num_unsolved_samples, 0 );
+    // The pattern DB is inconsistent, which it says, so that's no violation
+    assert!( report.num_inconsistent_moves > 0 && report.max_move_change > 1 );
+  }
+
+  #[test]
+  fn overestimate_is_inadmissible( ) {
+    // Keep all counterexamples, as the walks first find moves that change it
+    // by 2
+    let config = VerifyConfig { max_counterexamples: usize::MAX, ..VerifyConfig::default( ) };
+    let report = verify_heuristic( &DoubleManhattan( ManhattanDistance::new( ) ), &config );
+
+    assert!( !report.is_ok( ) );
+    assert_eq!( report.max_move_change, 2 );
+    assert_eq!( report.counterexamples.len( ), report.num_violations );
+    assert!( report.counterexamples.iter( ).any( |c| matches!( c, Counterexample::Inadmissible { .. } ) ) );
+  }
+
+  #[test]
+  fn false_consistency_claim_is_inconsistent( ) {
+    let report = verify_heuristic( &ClaimsConsistent( pattern_db( ) ), &VerifyConfig::default( ) );
+
+    assert!( !report.is_ok( ) );
+    assert_eq!( report.num_violations, report.num_inconsistent_moves );
+    assert!( report.counterexamples.iter( ).all( |c| matches!( c, Counterexample::Inconsistent { .. } ) ) );
+  }
+}